@@ -8,6 +8,10 @@ export interface FlywheelMetrics {
   averageCycleVolume: bigint;
   flywheelVelocity: number;
   growthRate: number;
+  epochVolume: bigint;
+  previousEpochVolume: bigint;
+  activeVolumeTier: number;
+  rateScaleBps: number;
 }
 
 /**
@@ -41,9 +45,29 @@ export function calculateFlywheelMetrics(state: MineballState): FlywheelMetrics
     averageCycleVolume,
     flywheelVelocity,
     growthRate,
+    epochVolume: state.epochVolume,
+    previousEpochVolume: state.previousEpochVolume,
+    ...activeVolumeTier(state),
   };
 }
 
+/**
+ * Volume tier the last complete epoch put in force (mirrors `VolumeTierTable::tier_for`)
+ */
+export function activeVolumeTier(
+  state: MineballState
+): { activeVolumeTier: number; rateScaleBps: number } {
+  const tiers = state.volumeTiers.tiers.slice(0, state.volumeTiers.count);
+  
+  for (let index = tiers.length - 1; index >= 0; index--) {
+    if (state.previousEpochVolume >= tiers[index].minVolume) {
+      return { activeVolumeTier: index, rateScaleBps: tiers[index].rateScaleBps };
+    }
+  }
+  
+  return { activeVolumeTier: 0, rateScaleBps: 10000 };
+}
+
 /**
  * Calculate the health of the flywheel (0-100 score)
  */
@@ -53,7 +77,8 @@ export function calculateFlywheelHealth(state: MineballState): number {
   // Pool balances (30 points)
   const poolScore = Math.min(30, 
     (Number(state.miningRewardPool) / Number(state.rewardDistributionThreshold)) * 10 +
-    (Number(state.buybackPool) / Number(state.buybackThreshold)) * 10 +
+    // The buyback threshold is in quote units, so only count whether tax is waiting
+    (state.buybackPool > BigInt(0) ? 10 : 0) +
     (Number(state.liquidityPool) / Number(state.liquidityThreshold)) * 10
   );
   score += poolScore;
@@ -24,7 +24,9 @@ export interface ProtocolMetrics {
   totalLiquidityAdded: bigint;
   
   // Tax metrics
-  taxRate: number;
+  buyTaxRate: number;
+  sellTaxRate: number;
+  transferTaxRate: number;
   effectiveTaxCollected: bigint;
 }
 
@@ -64,7 +66,9 @@ export function calculateProtocolMetrics(state: MineballState): ProtocolMetrics
     totalRewardsDistributed: state.totalRewardsDistributed,
     totalBuybacksExecuted: state.totalBuybackExecuted,
     totalLiquidityAdded: state.totalLiquidityAdded,
-    taxRate: state.taxRate,
+    buyTaxRate: state.buyTaxRate,
+    sellTaxRate: state.sellTaxRate,
+    transferTaxRate: state.transferTaxRate,
     effectiveTaxCollected,
   };
 }
@@ -6,12 +6,31 @@ export const MINEBALL_PROGRAM_ID = new PublicKey('Mine11111111111111111111111111
 // Seeds for PDA derivation
 export const STATE_SEED = 'mineball-state';
 export const HOLDER_SEED = 'holder';
+export const FEE_EXEMPTION_SEED = 'fee-exemption';
+export const REGISTERED_POOL_SEED = 'registered-pool';
 export const REWARD_POOL_SEED = 'reward-pool';
 export const BUYBACK_POOL_SEED = 'buyback-pool';
 export const LIQUIDITY_POOL_SEED = 'liquidity-pool';
+export const REWARD_EPOCH_SEED = 'reward-epoch';
+export const MINING_CYCLE_SEED = 'mining-cycle';
+export const BUYBACK_VAULT_SEED = 'buyback-vault';
+export const LIQUIDITY_QUOTE_VAULT_SEED = 'liquidity-quote-vault';
+export const LP_VAULT_SEED = 'lp-vault';
+export const KEEPER_REGISTRY_SEED = 'keeper-registry';
+export const KEEPER_SEED = 'keeper';
+export const PENDING_CONFIG_SEED = 'pending-config';
+export const VESTING_SEED = 'vesting';
+export const STAKE_SEED = 'stake';
+export const STAKE_VAULT_SEED = 'stake-vault';
 
 // Tokenomics constants
 export const MAX_TAX_RATE = 1000; // 10% in basis points
+export const MAX_LAUNCH_TAX_RATE = 2500; // 25% in basis points
+export const MAX_KEEPER_BOUNTY_BPS = 100; // 1% in basis points
+export const MIN_TRADING_LIMIT_BPS = 10; // 0.1% of supply
+export const MAX_SELL_COOLDOWN = 3600; // 1 hour
+export const VOLUME_EPOCH_DURATION = 86400; // 1 day
+export const MAX_VOLUME_TIERS = 4;
 export const BASIS_POINTS = 10000;
 export const DEFAULT_TAX_RATE = 600; // 6%
 
@@ -23,11 +42,27 @@ export const DEFAULT_BURN_SHARE = 1000; // 10%
 
 // Thresholds
 export const DEFAULT_REWARD_THRESHOLD = BigInt(1_000_000_000_000); // 1M with 6 decimals
-export const DEFAULT_BUYBACK_THRESHOLD = BigInt(500_000_000_000);
 export const DEFAULT_LIQUIDITY_THRESHOLD = BigInt(500_000_000_000);
 
 // Timing
+export const MIN_CYCLE_DURATION = 3600; // 1 hour in seconds
 export const MIN_CLAIM_INTERVAL = 3600; // 1 hour in seconds
+export const MAX_VESTING_DURATION = 31_536_000; // 365 days in seconds
+export const MAX_UNSTAKE_COOLDOWN = 2_592_000; // 30 days in seconds
+export const MIN_CONFIG_DELAY = 172800; // 48 hours in seconds
+export const MAX_PAUSE_DURATION = 604800; // 7 days in seconds
+
+// Pause bitflags
+export const PAUSE_TRANSFERS = 1 << 0;
+export const PAUSE_CLAIMS = 1 << 1;
+export const PAUSE_BUYBACK = 1 << 2;
+export const PAUSE_LIQUIDITY = 1 << 3;
+export const PAUSE_DISTRIBUTION = 1 << 4;
+export const PAUSE_STAKING = 1 << 5;
+
+// Merkle reward epochs
+export const MAX_EPOCH_RECIPIENTS = 80_000;
+export const EPOCH_CLAIM_WINDOW = 7_776_000; // 90 days
 
 // Token metadata
 export const TOKEN_NAME = 'mineball';
@@ -2,24 +2,56 @@ import { PublicKey } from '@solana/web3.js';
 
 export interface MineballState {
   authority: PublicKey;
+  pendingAuthority: PublicKey;
+  guardian: PublicKey;
+  paused: number;
+  pausedAt: number;
   mint: PublicKey;
   totalSupply: bigint;
-  taxRate: number;
-  miningRewardShare: number;
-  buybackShare: number;
-  liquidityShare: number;
-  burnShare: number;
+  taxMode: TaxMode;
+  buyTaxRate: number;
+  sellTaxRate: number;
+  transferTaxRate: number;
+  launchTax: LaunchTaxSchedule;
+  maxTxAmount: bigint;
+  maxWalletBalance: bigint;
+  minSecondsBetweenSells: number;
+  limitsLifted: boolean;
+  volumeEpochStart: number;
+  epochVolume: bigint;
+  previousEpochVolume: bigint;
+  volumeTiers: VolumeTierTable;
+  buyShares: TaxShares;
+  sellShares: TaxShares;
+  transferShares: TaxShares;
+  keeperBountyBps: number;
+  vestingDuration: number;
+  unstakeCooldown: number;
   miningRewardPool: bigint;
   buybackPool: bigint;
   liquidityPool: bigint;
   totalBurned: bigint;
+  boughtBack: bigint;
+  unclaimedRewards: bigint;
+  vestingRewards: bigint;
+  compoundedRewards: bigint;
+  totalStaked: bigint;
   totalMiningCycles: bigint;
   totalVolume: bigint;
   totalRewardsDistributed: bigint;
   totalBuybackExecuted: bigint;
   totalLiquidityAdded: bigint;
+  accRewardPerShare: bigint;
+  totalRewardWeight: bigint;
+  rewardHolders: bigint;
+  epochCount: bigint;
+  quoteMint: PublicKey;
+  ammProgram: PublicKey;
+  ammPool: PublicKey;
+  burnBuybacks: boolean;
+  lpMint: PublicKey;
   rewardDistributionThreshold: bigint;
-  buybackThreshold: bigint;
+  buybackThreshold: bigint; // quote-mint units
   liquidityThreshold: bigint;
   createdAt: number;
   lastRewardDistribution: number;
@@ -33,6 +65,153 @@ export interface HolderInfo {
   totalRewardsEarned: bigint;
   lastClaim: number;
   pendingRewards: bigint;
+  rewardBalance: bigint;
+  rewardDebt: bigint;
+  lastSellAt: number;
+  compound: boolean;
+  compoundedBalance: bigint;
+  bump: number;
+}
+
+export interface LaunchTaxSchedule {
+  startRate: number;
+  duration: number;
+  stepSeconds: number;
+}
+
+export interface VolumeTier {
+  minVolume: bigint;
+  rateScaleBps: number;
+}
+
+export interface VolumeTierTable {
+  count: number;
+  tiers: VolumeTier[];
+}
+
+export interface TaxShares {
+  miningRewardShare: number;
+  buybackShare: number;
+  liquidityShare: number;
+  burnShare: number;
+}
+
+export type TaxMode = 'instruction' | 'transferHook' | 'transferFee';
+
+export type TransferDirection = 'transfer' | 'buy' | 'sell';
+
+export interface RewardEpoch {
+  epoch: bigint;
+  cycle: bigint;
+  merkleRoot: number[];
+  totalAmount: bigint;
+  claimedAmount: bigint;
+  recipients: number;
+  claimedCount: number;
+  createdAt: number;
+  bump: number;
+  claimedBitmap: number[];
+}
+
+export interface MiningCycle {
+  cycle: bigint;
+  startedAt: number;
+  completedAt: number;
+  startVolume: bigint;
+  startRewardsDistributed: bigint;
+  startBuybackExecuted: bigint;
+  startLiquidityAdded: bigint;
+  volume: bigint;
+  rewardsDistributed: bigint;
+  buybackExecuted: bigint;
+  liquidityAdded: bigint;
+  payer: PublicKey;
+  bump: number;
+}
+
+export interface VestingSchedule {
+  holder: PublicKey;
+  lockedAmount: bigint;
+  unlockedAmount: bigint;
+  start: number;
+  end: number;
+  totalWithdrawn: bigint;
+  totalForfeited: bigint;
+  bump: number;
+}
+
+export interface StakePosition {
+  holder: PublicKey;
+  amount: bigint;
+  pendingUnstake: bigint;
+  unstakeAvailableAt: number;
+  lastStakedAt: number;
+  bump: number;
+}
+
+export interface CrankParams {
+  minBuybackOut: bigint;
+  minQuoteOut: bigint;
+  minLpOut: bigint;
+  deadline: number;
+}
+
+export interface KeeperRegistry {
+  permissioned: boolean;
+  minBond: bigint;
+  maxSlippageBps: number;
+  slashBps: number;
+  cooldownSeconds: number;
+  keeperCount: bigint;
+  totalSlashed: bigint;
+  referencePrice: bigint; // quote per token, scaled by 1e12
+  priceObservedAt: number;
+  bump: number;
+}
+
+export interface Keeper {
+  keeper: PublicKey;
+  bond: bigint;
+  registeredAt: number;
+  cooldownUntil: number;
+  triggersExecuted: bigint;
+  slashCount: number;
+  bump: number;
+}
+
+export interface ConfigUpdate {
+  buyTaxRate: number | null;
+  sellTaxRate: number | null;
+  transferTaxRate: number | null;
+  buyShares: TaxShares | null;
+  sellShares: TaxShares | null;
+  transferShares: TaxShares | null;
+  volumeTiers: VolumeTierTable | null;
+  keeperBountyBps: number | null;
+  vestingDuration: number | null;
+  unstakeCooldown: number | null;
+  rewardDistributionThreshold: bigint | null;
+  buybackThreshold: bigint | null;
+  liquidityThreshold: bigint | null;
+}
+
+export interface PendingConfigChange {
+  update: ConfigUpdate;
+  queuedAt: number;
+  eta: number;
+  bump: number;
+}
+
+export interface FeeExemption {
+  address: PublicKey;
+  discountBps: number;
+  createdAt: number;
+  bump: number;
+}
+
+export interface RegisteredPool {
+  tokenAccount: PublicKey;
+  registeredAt: number;
   bump: number;
 }
 
@@ -41,6 +220,27 @@ export interface TransferWithTaxEvent {
   to: PublicKey;
   amount: bigint;
   taxAmount: bigint;
+  taxRate: number;
+  direction: TransferDirection;
+  volumeTier: number;
+  timestamp: number;
+}
+
+export interface ExemptTransferEvent {
+  from: PublicKey;
+  to: PublicKey;
+  amount: bigint;
+  taxAmount: bigint;
+  taxRate: number;
+  direction: TransferDirection;
+  volumeTier: number;
+  discountBps: number;
+  timestamp: number;
+}
+
+export interface VolumeEpochRolledEvent {
+  previousEpochVolume: bigint;
+  volumeTier: number;
   timestamp: number;
 }
 
@@ -48,6 +248,8 @@ export interface RewardsDistributedEvent {
   cycle: bigint;
   amount: bigint;
   recipients: bigint;
+  keeper: PublicKey;
+  keeperBounty: bigint;
   timestamp: number;
 }
 
@@ -55,21 +257,45 @@ export interface BuybackExecutedEvent {
   cycle: bigint;
   amount: bigint;
   tokensBought: bigint;
+  taxShare: bigint;
+  tokensBurned: bigint;
+  keeper: PublicKey;
+  keeperBounty: bigint;
   timestamp: number;
 }
 
 export interface LiquidityAddedEvent {
   cycle: bigint;
   amount: bigint;
+  quoteAmount: bigint;
+  lpMinted: bigint;
+  keeper: PublicKey;
+  keeperBounty: bigint;
   timestamp: number;
 }
 
 export interface MiningCycleCompletedEvent {
   cycle: bigint;
-  totalVolume: bigint;
+  startedAt: number;
+  volume: bigint;
   rewardsDistributed: bigint;
   buybackExecuted: bigint;
   liquidityAdded: bigint;
+  keeper: PublicKey;
+  timestamp: number;
+}
+
+export interface MiningCycleClosedEvent {
+  cycle: bigint;
+  payer: PublicKey;
+  timestamp: number;
+}
+
+export interface PoolsSyncedEvent {
+  rewardSurplus: bigint;
+  buybackSurplus: bigint;
+  liquiditySurplus: bigint;
+  swept: bigint;
   timestamp: number;
 }
 
@@ -78,3 +304,73 @@ export interface RewardsClaimedEvent {
   amount: bigint;
   timestamp: number;
 }
+
+export interface StakedEvent {
+  holder: PublicKey;
+  amount: bigint;
+  stakedAmount: bigint;
+  timestamp: number;
+}
+
+export interface UnstakedEvent {
+  holder: PublicKey;
+  amount: bigint;
+  stakedAmount: bigint;
+  availableAt: number;
+  timestamp: number;
+}
+
+export interface UnstakeWithdrawnEvent {
+  holder: PublicKey;
+  amount: bigint;
+  timestamp: number;
+}
+
+export interface RewardsCompoundedEvent {
+  holder: PublicKey;
+  amount: bigint;
+  compoundedBalance: bigint;
+  caller: PublicKey;
+  timestamp: number;
+}
+
+export interface RewardsVestedEvent {
+  holder: PublicKey;
+  amount: bigint;
+  lockedAmount: bigint;
+  vestingEnd: number;
+  timestamp: number;
+}
+
+export interface VestedWithdrawnEvent {
+  holder: PublicKey;
+  amount: bigint;
+  forfeited: bigint;
+  timestamp: number;
+}
+
+export interface KeeperSlashedEvent {
+  keeper: PublicKey;
+  minAmountOut: bigint;
+  requiredMinOut: bigint;
+  slashed: bigint;
+  cooldownUntil: number;
+  timestamp: number;
+}
+
+export interface ConfigChangeQueuedEvent {
+  update: ConfigUpdate;
+  eta: number;
+  timestamp: number;
+}
+
+export interface ConfigChangeCancelledEvent {
+  eta: number;
+  timestamp: number;
+}
+
+export interface PauseUpdatedEvent {
+  paused: number;
+  caller: PublicKey;
+  timestamp: number;
+}
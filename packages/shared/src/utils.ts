@@ -2,11 +2,26 @@ import { PublicKey } from '@solana/web3.js';
 import {
   STATE_SEED,
   HOLDER_SEED,
+  FEE_EXEMPTION_SEED,
+  REGISTERED_POOL_SEED,
   REWARD_POOL_SEED,
   BUYBACK_POOL_SEED,
   LIQUIDITY_POOL_SEED,
+  REWARD_EPOCH_SEED,
+  MINING_CYCLE_SEED,
+  BUYBACK_VAULT_SEED,
+  LIQUIDITY_QUOTE_VAULT_SEED,
+  LP_VAULT_SEED,
+  KEEPER_REGISTRY_SEED,
+  KEEPER_SEED,
+  PENDING_CONFIG_SEED,
+  VESTING_SEED,
+  STAKE_SEED,
+  STAKE_VAULT_SEED,
+  VOLUME_EPOCH_DURATION,
   MINEBALL_PROGRAM_ID,
 } from './constants';
+import { MineballState, TransferDirection } from './types';
 
 /**
  * Derive the mineball state PDA
@@ -58,6 +73,140 @@ export function deriveLiquidityPoolPDA(): [PublicKey, number] {
   );
 }
 
+/**
+ * Derive the buyback quote vault PDA
+ */
+export function deriveBuybackVaultPDA(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(BUYBACK_VAULT_SEED)],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the quote vault add_liquidity pairs the liquidity pool with
+ */
+export function deriveLiquidityQuoteVaultPDA(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(LIQUIDITY_QUOTE_VAULT_SEED)],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the LP vault PDA holding the protocol's locked LP tokens
+ */
+export function deriveLpVaultPDA(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(LP_VAULT_SEED)],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the keeper registry PDA
+ */
+export function deriveKeeperRegistryPDA(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(KEEPER_REGISTRY_SEED)],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive a keeper's PDA
+ */
+export function deriveKeeperPDA(keeper: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(KEEPER_SEED), keeper.toBuffer()],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the fee exemption PDA for an address
+ */
+export function deriveFeeExemptionPDA(address: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(FEE_EXEMPTION_SEED), address.toBuffer()],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the registered pool PDA for an AMM pool token account
+ */
+export function deriveRegisteredPoolPDA(tokenAccount: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(REGISTERED_POOL_SEED), tokenAccount.toBuffer()],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the pending config change PDA
+ */
+export function derivePendingConfigPDA(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(PENDING_CONFIG_SEED)],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the PDA of a Merkle reward epoch, numbered from 1 by `epochCount`
+ */
+export function deriveRewardEpochPDA(epoch: bigint): [PublicKey, number] {
+  const epochBuffer = Buffer.alloc(8);
+  epochBuffer.writeBigUInt64LE(epoch);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(REWARD_EPOCH_SEED), epochBuffer],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the PDA recording one mining cycle
+ */
+export function deriveMiningCyclePDA(cycle: bigint): [PublicKey, number] {
+  const cycleBuffer = Buffer.alloc(8);
+  cycleBuffer.writeBigUInt64LE(cycle);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(MINING_CYCLE_SEED), cycleBuffer],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive a holder's vesting schedule PDA
+ */
+export function deriveVestingSchedulePDA(holder: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(VESTING_SEED), holder.toBuffer()],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive a holder's stake position PDA
+ */
+export function deriveStakePositionPDA(holder: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(STAKE_SEED), holder.toBuffer()],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
+/**
+ * Derive the vault staked tokens are held in
+ */
+export function deriveStakeVaultPDA(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(STAKE_VAULT_SEED)],
+    MINEBALL_PROGRAM_ID
+  );
+}
+
 /**
  * Format a token amount with decimals
  */
@@ -84,6 +233,53 @@ export function calculateTax(amount: bigint, taxRate: number): bigint {
   return (amount * BigInt(taxRate)) / BigInt(10000);
 }
 
+/**
+ * Rate scale of the volume tier in force at `now`
+ * (mirrors `MineballState::active_volume_tier`)
+ */
+export function volumeTierScale(state: MineballState, now: number): number {
+  const elapsed = now - state.volumeEpochStart;
+  const volume = elapsed < VOLUME_EPOCH_DURATION
+    ? state.previousEpochVolume
+    : elapsed < 2 * VOLUME_EPOCH_DURATION
+      ? state.epochVolume
+      : BigInt(0);
+  
+  const tiers = state.volumeTiers.tiers.slice(0, state.volumeTiers.count);
+  const tier = [...tiers].reverse().find((t) => volume >= t.minVolume);
+  
+  return tier ? tier.rateScaleBps : 10000;
+}
+
+/**
+ * Tax rate a transfer in `direction` pays at `now`, including the launch tax
+ * (mirrors `MineballState::effective_tax_rate`)
+ */
+export function calculateEffectiveTaxRate(
+  state: MineballState,
+  direction: TransferDirection,
+  now: number
+): number {
+  const unscaledRate = {
+    buy: state.buyTaxRate,
+    sell: state.sellTaxRate,
+    transfer: state.transferTaxRate,
+  }[direction];
+  const baseRate = Math.floor((unscaledRate * volumeTierScale(state, now)) / 10000);
+  const { startRate, duration, stepSeconds } = state.launchTax;
+  
+  let elapsed = Math.max(now - state.createdAt, 0);
+  if (startRate <= baseRate || elapsed >= duration) {
+    return baseRate;
+  }
+  
+  if (stepSeconds > 0) {
+    elapsed -= elapsed % stepSeconds;
+  }
+  
+  return baseRate + Math.floor(((startRate - baseRate) * (duration - elapsed)) / duration);
+}
+
 /**
  * Calculate share of a pool
  */
@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use mineball::{
+    program::Mineball, FEE_EXEMPTION_SEED, REGISTERED_POOL_SEED, STATE_SEED, TAX_LEDGER_SEED,
+};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
+
+declare_id!("MineHook11111111111111111111111111111111111");
+
+/// Seed of the extra-account-meta list, fixed by the transfer hook interface
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// Index of the mineball program in the `Execute` account list
+/// (source, mint, destination, owner, meta list, then our extra accounts)
+const MINEBALL_PROGRAM_INDEX: u8 = 5;
+
+#[program]
+pub mod mineball_hook {
+    use super::*;
+
+    /// Create the extra-account-meta list Token-2022 reads on every transfer
+    /// Resolves the mineball program, its state, both fee exemptions, both pool registrations
+    /// and the tax ledgers of both token accounts
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        let account_metas = extra_account_metas()?;
+
+        let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
+        let lamports = Rent::get()?.minimum_balance(account_size);
+
+        let mint = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            EXTRA_ACCOUNT_METAS_SEED,
+            mint.as_ref(),
+            &[ctx.bumps.extra_account_meta_list],
+        ]];
+
+        create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.extra_account_meta_list.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            lamports,
+            account_size as u64,
+            ctx.program_id,
+        )?;
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &account_metas,
+        )?;
+
+        msg!("Extra account metas initialized for {}", mint);
+
+        Ok(())
+    }
+
+    /// Runs on every transfer of the mint
+    /// Forwards the amount to mineball so the tax is recorded against the buyer on
+    /// buys and the sender otherwise
+    pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
+        // Transfers signed by the state PDA are protocol payouts and tax
+        // collection, and transfers into state-owned vaults are AMM payouts
+        // during a buyback. Calling back into mineball would be re-entrant.
+        if ctx.accounts.owner.key() == ctx.accounts.state.key()
+            || ctx.accounts.destination_token.owner == ctx.accounts.state.key()
+        {
+            return Ok(());
+        }
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.mineball_program.to_account_info(),
+            mineball::cpi::accounts::RecordHookTransfer {
+                state: ctx.accounts.state.to_account_info(),
+                source_token_account: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                destination_token_account: ctx.accounts.destination_token.to_account_info(),
+                source_tax_ledger: ctx.accounts.source_tax_ledger.to_account_info(),
+                destination_tax_ledger: ctx.accounts.destination_tax_ledger.to_account_info(),
+                source_exemption: ctx.accounts.source_exemption.to_account_info(),
+                destination_exemption: ctx.accounts.destination_exemption.to_account_info(),
+                source_pool: ctx.accounts.source_pool.to_account_info(),
+                destination_pool: ctx.accounts.destination_pool.to_account_info(),
+            },
+        );
+        mineball::cpi::record_hook_transfer(cpi_ctx, amount)
+    }
+
+    /// Token-2022 calls the hook with the interface discriminator rather than
+    /// an Anchor one, so route `Execute` to `transfer_hook` by hand
+    pub fn fallback<'info>(
+        program_id: &Pubkey,
+        accounts: &'info [AccountInfo<'info>],
+        data: &[u8],
+    ) -> Result<()> {
+        match TransferHookInstruction::unpack(data)? {
+            TransferHookInstruction::Execute { amount } => {
+                let amount_bytes = amount.to_le_bytes();
+                __private::__global::transfer_hook(program_id, accounts, &amount_bytes)
+            }
+            _ => Err(ProgramError::InvalidInstructionData.into()),
+        }
+    }
+}
+
+/// Accounts appended to every `Execute` call, in order
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    let (state, _) = Pubkey::find_program_address(&[STATE_SEED], &mineball::ID);
+
+    Ok(vec![
+        // 5: mineball program
+        ExtraAccountMeta::new_with_pubkey(&mineball::ID, false, false)?,
+        // 6: mineball state
+        ExtraAccountMeta::new_with_pubkey(&state, false, true)?,
+        // 7: tax ledger of the source token account, charged on sells and transfers
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            MINEBALL_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: TAX_LEDGER_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            true,
+        )?,
+        // 8: fee exemption of the source owner (owner sits at offset 32 of a token account)
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            MINEBALL_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: FEE_EXEMPTION_SEED.to_vec(),
+                },
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )?,
+        // 9: fee exemption of the destination owner
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            MINEBALL_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: FEE_EXEMPTION_SEED.to_vec(),
+                },
+                Seed::AccountData {
+                    account_index: 2,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )?,
+        // 10: pool registration of the source token account
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            MINEBALL_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: REGISTERED_POOL_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            false,
+        )?,
+        // 11: pool registration of the destination token account
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            MINEBALL_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: REGISTERED_POOL_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 2 },
+            ],
+            false,
+            false,
+        )?,
+        // 12: tax ledger of the destination token account, charged on buys
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            MINEBALL_PROGRAM_INDEX,
+            &[
+                Seed::Literal {
+                    bytes: TAX_LEDGER_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 2 },
+            ],
+            false,
+            true,
+        )?,
+    ])
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: created here with the seeds required by the transfer hook interface
+    #[account(
+        mut,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: source token account owner or delegate, only compared by key
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: extra-account-meta list, validated by seeds
+    #[account(
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mineball_program: Program<'info, Mineball>,
+
+    /// CHECK: mineball state PDA, validated by seeds and deserialized by mineball
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump,
+        seeds::program = mineball_program.key()
+    )]
+    pub state: UncheckedAccount<'info>,
+
+    /// CHECK: source token account's tax ledger, validated by mineball
+    #[account(mut)]
+    pub source_tax_ledger: UncheckedAccount<'info>,
+
+    /// CHECK: source owner's fee exemption, validated by mineball
+    pub source_exemption: UncheckedAccount<'info>,
+
+    /// CHECK: destination owner's fee exemption, validated by mineball
+    pub destination_exemption: UncheckedAccount<'info>,
+
+    /// CHECK: source token account's pool registration, validated by mineball
+    pub source_pool: UncheckedAccount<'info>,
+
+    /// CHECK: destination token account's pool registration, validated by mineball
+    pub destination_pool: UncheckedAccount<'info>,
+
+    /// CHECK: destination token account's tax ledger, validated by mineball
+    #[account(mut)]
+    pub destination_tax_ledger: UncheckedAccount<'info>,
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+/// Accounts of a constant-product `swap`, in the order the AMM expects them
+pub struct SwapAccounts<'info> {
+    pub pool: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub source: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub vault_in: AccountInfo<'info>,
+    pub vault_out: AccountInfo<'info>,
+    pub mint_in: AccountInfo<'info>,
+    pub mint_out: AccountInfo<'info>,
+    pub token_program_in: AccountInfo<'info>,
+    pub token_program_out: AccountInfo<'info>,
+}
+
+/// Accounts of a proportional `deposit`, in the order the AMM expects them.
+/// `x`/`y` may name the pool's mints in either order.
+pub struct DepositAccounts<'info> {
+    pub pool: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub source_x: AccountInfo<'info>,
+    pub source_y: AccountInfo<'info>,
+    pub vault_x: AccountInfo<'info>,
+    pub vault_y: AccountInfo<'info>,
+    pub lp_mint: AccountInfo<'info>,
+    pub lp_destination: AccountInfo<'info>,
+    pub mint_x: AccountInfo<'info>,
+    pub mint_y: AccountInfo<'info>,
+    pub token_program_x: AccountInfo<'info>,
+    pub token_program_y: AccountInfo<'info>,
+    pub lp_token_program: AccountInfo<'info>,
+}
+
+/// CPI into the configured AMM's `swap(amount_in, min_amount_out)`.
+///
+/// The instruction layouts are the ones implemented by `programs/mock-amm`, so any
+/// venue that speaks them (directly or through an adapter) can back the flywheel.
+pub fn swap<'info>(
+    amm_program: &AccountInfo<'info>,
+    accounts: SwapAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount_in: u64,
+    min_amount_out: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.pool.key(), false),
+        AccountMeta::new_readonly(accounts.authority.key(), true),
+        AccountMeta::new(accounts.source.key(), false),
+        AccountMeta::new(accounts.destination.key(), false),
+        AccountMeta::new(accounts.vault_in.key(), false),
+        AccountMeta::new(accounts.vault_out.key(), false),
+        AccountMeta::new_readonly(accounts.mint_in.key(), false),
+        AccountMeta::new_readonly(accounts.mint_out.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_in.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_out.key(), false),
+    ];
+    
+    let account_infos = vec![
+        accounts.pool,
+        accounts.authority,
+        accounts.source,
+        accounts.destination,
+        accounts.vault_in,
+        accounts.vault_out,
+        accounts.mint_in,
+        accounts.mint_out,
+        accounts.token_program_in,
+        accounts.token_program_out,
+    ];
+    
+    invoke_amm(
+        amm_program,
+        "swap",
+        &[amount_in, min_amount_out],
+        metas,
+        account_infos,
+        remaining_accounts,
+        signer_seeds,
+    )
+}
+
+/// CPI into the configured AMM's `deposit(amount_x, amount_y, min_lp_out)`
+pub fn deposit<'info>(
+    amm_program: &AccountInfo<'info>,
+    accounts: DepositAccounts<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount_x: u64,
+    amount_y: u64,
+    min_lp_out: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let metas = vec![
+        AccountMeta::new_readonly(accounts.pool.key(), false),
+        AccountMeta::new_readonly(accounts.authority.key(), true),
+        AccountMeta::new(accounts.source_x.key(), false),
+        AccountMeta::new(accounts.source_y.key(), false),
+        AccountMeta::new(accounts.vault_x.key(), false),
+        AccountMeta::new(accounts.vault_y.key(), false),
+        AccountMeta::new(accounts.lp_mint.key(), false),
+        AccountMeta::new(accounts.lp_destination.key(), false),
+        AccountMeta::new_readonly(accounts.mint_x.key(), false),
+        AccountMeta::new_readonly(accounts.mint_y.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_x.key(), false),
+        AccountMeta::new_readonly(accounts.token_program_y.key(), false),
+        AccountMeta::new_readonly(accounts.lp_token_program.key(), false),
+    ];
+    
+    let account_infos = vec![
+        accounts.pool,
+        accounts.authority,
+        accounts.source_x,
+        accounts.source_y,
+        accounts.vault_x,
+        accounts.vault_y,
+        accounts.lp_mint,
+        accounts.lp_destination,
+        accounts.mint_x,
+        accounts.mint_y,
+        accounts.token_program_x,
+        accounts.token_program_y,
+        accounts.lp_token_program,
+    ];
+    
+    invoke_amm(
+        amm_program,
+        "deposit",
+        &[amount_x, amount_y, min_lp_out],
+        metas,
+        account_infos,
+        remaining_accounts,
+        signer_seeds,
+    )
+}
+
+/// Build an Anchor-style instruction for `name` with u64 arguments and invoke it.
+/// Remaining accounts (transfer-hook extras) are appended as-is.
+fn invoke_amm<'info>(
+    amm_program: &AccountInfo<'info>,
+    name: &str,
+    args: &[u64],
+    mut metas: Vec<AccountMeta>,
+    mut account_infos: Vec<AccountInfo<'info>>,
+    remaining_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    for arg in args {
+        data.extend_from_slice(&arg.to_le_bytes());
+    }
+    
+    metas.extend(remaining_accounts.iter().map(|account| AccountMeta {
+        pubkey: account.key(),
+        is_signer: false,
+        is_writable: account.is_writable,
+    }));
+    account_infos.extend_from_slice(remaining_accounts);
+    
+    let instruction = Instruction {
+        program_id: amm_program.key(),
+        accounts: metas,
+        data,
+    };
+    invoke_signed(&instruction, &account_infos, signer_seeds)?;
+    
+    Ok(())
+}
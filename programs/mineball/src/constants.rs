@@ -1,24 +1,112 @@
+use anchor_lang::prelude::*;
+
 /// Maximum tax rate in basis points (10%)
 pub const MAX_TAX_RATE: u16 = 1000;
 
+/// Maximum starting rate of the launch tax (25%)
+pub const MAX_LAUNCH_TAX_RATE: u16 = 2500;
+
+/// Maximum keeper bounty in basis points (1%)
+pub const MAX_KEEPER_BOUNTY_BPS: u16 = 100;
+
+/// Smallest max-tx or max-wallet limit as a share of supply (0.1%)
+pub const MIN_TRADING_LIMIT_BPS: u16 = 10;
+
+/// Longest cooldown between two sells from one wallet (1 hour)
+pub const MAX_SELL_COOLDOWN: i64 = 3600;
+
 /// Basis points denominator
 pub const BASIS_POINTS: u16 = 10000;
 
 /// Default reward distribution threshold (1M tokens)
 pub const DEFAULT_REWARD_THRESHOLD: u64 = 1_000_000_000_000; // 1M with 6 decimals
 
-/// Default buyback threshold (500K tokens)
-pub const DEFAULT_BUYBACK_THRESHOLD: u64 = 500_000_000_000;
-
 /// Default liquidity threshold (500K tokens)
 pub const DEFAULT_LIQUIDITY_THRESHOLD: u64 = 500_000_000_000;
 
+/// Length of one volume epoch for the volume tiers (1 day)
+pub const VOLUME_EPOCH_DURATION: i64 = 86_400;
+
+/// Maximum entries in the volume tier table
+pub const MAX_VOLUME_TIERS: usize = 4;
+
+/// Shortest mining cycle `complete_cycle` will close (1 hour)
+pub const MIN_CYCLE_DURATION: i64 = 3600;
+
 /// Minimum claim interval (1 hour)
 pub const MIN_CLAIM_INTERVAL: i64 = 3600;
 
+/// Longest vesting period for claimed rewards (365 days)
+pub const MAX_VESTING_DURATION: i64 = 31_536_000;
+
+/// Longest wait between unstaking and withdrawing (30 days)
+pub const MAX_UNSTAKE_COOLDOWN: i64 = 2_592_000;
+
+/// Minimum notice before a queued config change can be executed (48 hours)
+pub const MIN_CONFIG_DELAY: i64 = 172_800;
+
+/// Fixed-point scale for the reward-per-token accumulator
+pub const ACC_REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Fixed-point scale of the keeper registry's reference price (quote per token)
+pub const PRICE_PRECISION: u128 = 1_000_000_000_000;
+
+/// Shortest gap between two `observe_price` updates (5 minutes)
+pub const PRICE_OBSERVATION_INTERVAL: i64 = 300;
+
+/// Share of the gap to the spot price the reference price closes per observation (10%)
+pub const PRICE_SMOOTHING_BPS: u16 = 1000;
+
+/// Maximum recipients in one Merkle reward epoch (claimed bitmap stays under 10KB)
+pub const MAX_EPOCH_RECIPIENTS: u32 = 80_000;
+
+/// How long holders have to claim from a Merkle reward epoch before it can be closed (90 days)
+pub const EPOCH_CLAIM_WINDOW: i64 = 7_776_000;
+
+/// Instruction groups the guardian can pause
+pub const PAUSE_TRANSFERS: u8 = 1 << 0;
+pub const PAUSE_CLAIMS: u8 = 1 << 1;
+pub const PAUSE_BUYBACK: u8 = 1 << 2;
+pub const PAUSE_LIQUIDITY: u8 = 1 << 3;
+pub const PAUSE_DISTRIBUTION: u8 = 1 << 4;
+pub const PAUSE_STAKING: u8 = 1 << 5;
+pub const PAUSE_ALL: u8 = PAUSE_TRANSFERS
+    | PAUSE_CLAIMS
+    | PAUSE_BUYBACK
+    | PAUSE_LIQUIDITY
+    | PAUSE_DISTRIBUTION
+    | PAUSE_STAKING;
+
+/// After this long since a pause started anyone can unpause, so a renounced
+/// protocol can't stay frozen (7 days)
+pub const MAX_PAUSE_DURATION: i64 = 604_800;
+
+/// Once the authority is renounced, a new pause can't start until this long after
+/// the previous one expired (7 days)
+pub const PAUSE_COOLDOWN: i64 = 604_800;
+
 /// Seeds for PDA derivation
 pub const STATE_SEED: &[u8] = b"mineball-state";
 pub const HOLDER_SEED: &[u8] = b"holder";
+pub const FEE_EXEMPTION_SEED: &[u8] = b"fee-exemption";
+pub const REGISTERED_POOL_SEED: &[u8] = b"registered-pool";
 pub const REWARD_POOL_SEED: &[u8] = b"reward-pool";
 pub const BUYBACK_POOL_SEED: &[u8] = b"buyback-pool";
 pub const LIQUIDITY_POOL_SEED: &[u8] = b"liquidity-pool";
+pub const TAX_LEDGER_SEED: &[u8] = b"tax-ledger";
+pub const REWARD_EPOCH_SEED: &[u8] = b"reward-epoch";
+pub const MINING_CYCLE_SEED: &[u8] = b"mining-cycle";
+pub const BUYBACK_VAULT_SEED: &[u8] = b"buyback-vault";
+pub const LIQUIDITY_QUOTE_VAULT_SEED: &[u8] = b"liquidity-quote-vault";
+pub const LP_VAULT_SEED: &[u8] = b"lp-vault";
+pub const KEEPER_REGISTRY_SEED: &[u8] = b"keeper-registry";
+pub const KEEPER_SEED: &[u8] = b"keeper";
+pub const PENDING_CONFIG_SEED: &[u8] = b"pending-config";
+pub const VESTING_SEED: &[u8] = b"vesting";
+pub const STAKE_SEED: &[u8] = b"stake";
+pub const STAKE_VAULT_SEED: &[u8] = b"stake-vault";
+
+/// Companion program registered as the Token-2022 transfer hook.
+/// Kept separate because a program cannot be re-entered by its own hook
+/// while it is moving the mint out of the protocol vaults.
+pub const TRANSFER_HOOK_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("MineHook11111111111111111111111111111111111");
@@ -31,4 +31,163 @@ pub enum MineballError {
     
     #[msg("Invalid amount")]
     InvalidAmount,
+    
+    #[msg("Instruction is not available in the current tax mode")]
+    InvalidTaxMode,
+    
+    #[msg("Mint extensions are not configured for the mineball transfer hook")]
+    InvalidMintExtensions,
+    
+    #[msg("Transfer hook invoked outside of a token transfer")]
+    NotTransferring,
+    
+    #[msg("Remaining balance would not cover the tax owed")]
+    TaxDebtUncollectable,
+    
+    #[msg("No tax owed by this account")]
+    NoTaxOwed,
+    
+    #[msg("No withheld transfer fees to harvest")]
+    NothingToHarvest,
+    
+    #[msg("No holder balance is registered for rewards")]
+    NoRewardWeight,
+    
+    #[msg("Reward epoch account does not match the distribution mode")]
+    InvalidRewardEpoch,
+    
+    #[msg("Invalid number of epoch recipients")]
+    InvalidRecipientCount,
+    
+    #[msg("Merkle proof does not match the epoch root")]
+    InvalidMerkleProof,
+    
+    #[msg("Epoch reward already claimed")]
+    AlreadyClaimed,
+    
+    #[msg("Quote mint must differ from the protocol mint")]
+    InvalidQuoteMint,
+    
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    
+    #[msg("Swap output below the minimum amount")]
+    SlippageExceeded,
+    
+    #[msg("LP mint does not belong to the configured AMM pool")]
+    InvalidLpMint,
+    
+    #[msg("Invalid keeper registry configuration")]
+    InvalidKeeperConfig,
+    
+    #[msg("Keeper bond below the registry minimum")]
+    InsufficientKeeperBond,
+    
+    #[msg("Keeper is in cooldown")]
+    KeeperInCooldown,
+    
+    #[msg("Invalid keeper bounty (must be at most 100 basis points)")]
+    InvalidKeeperBounty,
+    
+    #[msg("Authority has been renounced")]
+    AuthorityRenounced,
+    
+    #[msg("Config change delay is shorter than the minimum")]
+    ConfigDelayTooShort,
+    
+    #[msg("Config change is still timelocked")]
+    ConfigChangeNotReady,
+    
+    #[msg("Instruction is paused")]
+    ProtocolPaused,
+    
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    
+    #[msg("Invalid fee exemption discount (must be 1 to 10000 basis points)")]
+    InvalidExemption,
+    
+    #[msg("Invalid launch tax schedule")]
+    InvalidLaunchTax,
+    
+    #[msg("Transfer exceeds the maximum transaction amount")]
+    MaxTransactionExceeded,
+    
+    #[msg("Transfer would exceed the maximum wallet balance")]
+    MaxWalletExceeded,
+    
+    #[msg("Sell cooldown has not elapsed")]
+    SellCooldownActive,
+    
+    #[msg("Invalid trading limits")]
+    InvalidTradingLimits,
+    
+    #[msg("Trading limits have been lifted permanently")]
+    TradingLimitsLifted,
+    
+    #[msg("Invalid volume tiers (must start at zero volume and ascend)")]
+    InvalidVolumeTiers,
+    
+    #[msg("Mining cycle has not run its minimum duration")]
+    CycleNotComplete,
+    
+    #[msg("No trigger is ready to run")]
+    NothingToCrank,
+    
+    #[msg("Pool vault holds less than its accounted balance")]
+    PoolBalanceMismatch,
+    
+    #[msg("Token account is not of the protocol mint")]
+    InvalidMint,
+    
+    #[msg("Token account is not owned by the expected wallet")]
+    InvalidOwner,
+    
+    #[msg("Account does not belong to the configured AMM")]
+    InvalidAmm,
+    
+    #[msg("Invalid vesting duration")]
+    InvalidVestingDuration,
+    
+    #[msg("Vesting is enabled, pass the holder's vesting schedule")]
+    VestingScheduleRequired,
+    
+    #[msg("Nothing to withdraw from the vesting schedule")]
+    NothingToWithdraw,
+    
+    #[msg("Holder has not opted into compounding")]
+    CompoundingDisabled,
+    
+    #[msg("Invalid unstake cooldown")]
+    InvalidUnstakeCooldown,
+    
+    #[msg("Not enough tokens staked")]
+    InsufficientStake,
+    
+    #[msg("Unstaked tokens are still cooling down")]
+    UnstakeCooldownActive,
+    
+    #[msg("The taxed token account has no tax ledger")]
+    TaxLedgerRequired,
+    
+    #[msg("The guardian must wait out the pause cooldown")]
+    PauseCooldownActive,
+    
+    #[msg("The reference price was observed too recently")]
+    PriceObservationTooSoon,
+    
+    #[msg("No slashed bonds to withdraw")]
+    NoSlashedBonds,
+    
+    #[msg("The reward epoch's claim window has closed")]
+    EpochExpired,
+    
+    #[msg("The reward epoch's claim window is still open")]
+    EpochNotExpired,
+    
+    #[msg("Mining cycle is still open")]
+    CycleStillOpen,
+    
+    #[msg("Rent can only be refunded to the account that paid it")]
+    InvalidRentPayer,
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::{ConfigUpdate, TaxShares, TransferDirection, VolumeTierTable};
 
 /// Emitted when mining rewards are distributed
 #[event]
@@ -6,6 +7,8 @@ pub struct RewardsDistributed {
     pub cycle: u64,
     pub amount: u64,
     pub recipients: u64,
+    pub keeper: Pubkey,
+    pub keeper_bounty: u64,
     pub timestamp: i64,
 }
 
@@ -15,6 +18,18 @@ pub struct BuybackExecuted {
     pub cycle: u64,
     pub amount: u64,
     pub tokens_bought: u64,
+    pub tax_share: u64,
+    pub tokens_burned: u64,
+    pub keeper: Pubkey,
+    pub keeper_bounty: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when quote is deposited into the buyback vault
+#[event]
+pub struct BuybackDeposited {
+    pub depositor: Pubkey,
+    pub amount: u64,
     pub timestamp: i64,
 }
 
@@ -23,17 +38,31 @@ pub struct BuybackExecuted {
 pub struct LiquidityAdded {
     pub cycle: u64,
     pub amount: u64,
+    pub quote_amount: u64,
+    pub lp_minted: u64,
+    pub keeper: Pubkey,
+    pub keeper_bounty: u64,
     pub timestamp: i64,
 }
 
-/// Emitted when a mining cycle completes
+/// Emitted when a mining cycle completes, with what happened during the cycle
 #[event]
 pub struct MiningCycleCompleted {
     pub cycle: u64,
-    pub total_volume: u64,
+    pub started_at: i64,
+    pub volume: u64,
     pub rewards_distributed: u64,
     pub buyback_executed: u64,
     pub liquidity_added: u64,
+    pub keeper: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a completed mining cycle is closed and its rent refunded
+#[event]
+pub struct MiningCycleClosed {
+    pub cycle: u64,
+    pub payer: Pubkey,
     pub timestamp: i64,
 }
 
@@ -44,6 +73,108 @@ pub struct TransferWithTaxExecuted {
     pub to: Pubkey,
     pub amount: u64,
     pub tax_amount: u64,
+    pub tax_rate: u16,
+    pub direction: TransferDirection,
+    pub volume_tier: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted instead of `TransferWithTaxExecuted` when either side holds a fee exemption.
+/// Fully exempt transfers are not counted in `total_volume`.
+#[event]
+pub struct ExemptTransferExecuted {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub tax_amount: u64,
+    pub tax_rate: u16,
+    pub direction: TransferDirection,
+    pub volume_tier: u8,
+    pub discount_bps: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when a fee exemption is set
+#[event]
+pub struct FeeExemptionSet {
+    pub address: Pubkey,
+    pub discount_bps: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when a fee exemption is removed
+#[event]
+pub struct FeeExemptionRemoved {
+    pub address: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when an AMM pool token account is registered
+#[event]
+pub struct PoolRegistered {
+    pub token_account: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when an AMM pool token account is unregistered
+#[event]
+pub struct PoolUnregistered {
+    pub token_account: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a new volume epoch starts, with the tier it puts in force
+#[event]
+pub struct VolumeEpochRolled {
+    pub previous_epoch_volume: u64,
+    pub volume_tier: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when the trading limits change
+#[event]
+pub struct TradingLimitsUpdated {
+    pub max_tx_amount: u64,
+    pub max_wallet_balance: u64,
+    pub min_seconds_between_sells: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the trading limits are lifted for good
+#[event]
+pub struct TradingLimitsRemoved {
+    pub timestamp: i64,
+}
+
+/// Emitted when tax recorded by the transfer hook is collected. `written_off` is
+/// the part of the debt the account's balance could no longer cover.
+#[event]
+pub struct HookTaxCollected {
+    pub token_account: Pubkey,
+    pub amount: u64,
+    pub burned: u64,
+    pub written_off: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when withheld transfer fees are harvested and split across the pools
+#[event]
+pub struct FeesHarvested {
+    pub amount: u64,
+    pub mining_reward_amount: u64,
+    pub buyback_amount: u64,
+    pub liquidity_amount: u64,
+    pub burn_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `sync_pools` sweeps vault surpluses into the mining reward pool
+#[event]
+pub struct PoolsSynced {
+    pub reward_surplus: u64,
+    pub buyback_surplus: u64,
+    pub liquidity_surplus: u64,
+    pub swept: u64,
     pub timestamp: i64,
 }
 
@@ -54,3 +185,178 @@ pub struct RewardsClaimed {
     pub amount: u64,
     pub timestamp: i64,
 }
+
+/// Emitted when an expired Merkle reward epoch is closed and its remainder recycled
+#[event]
+pub struct RewardEpochClosed {
+    pub epoch: u64,
+    pub cycle: u64,
+    pub unclaimed: u64,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a holder stakes tokens
+#[event]
+pub struct Staked {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub staked_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a holder unstakes; `available_at` is when the tokens can be
+/// withdrawn, or zero when they were paid out at once
+#[event]
+pub struct Unstaked {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub staked_amount: u64,
+    pub available_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when unstaked tokens are withdrawn after their cooldown
+#[event]
+pub struct UnstakeWithdrawn {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a holder's rewards are restaked instead of paid out
+#[event]
+pub struct RewardsCompounded {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub compounded_balance: u64,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a claim is locked into the holder's vesting schedule
+#[event]
+pub struct RewardsVested {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub locked_amount: u64,
+    pub vesting_end: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a holder withdraws vested rewards, forfeiting any unvested ones
+#[event]
+pub struct VestedWithdrawn {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub forfeited: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a keeper joins the registry
+#[event]
+pub struct KeeperRegistered {
+    pub keeper: Pubkey,
+    pub bond: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a keeper leaves the registry and its bond is returned
+#[event]
+pub struct KeeperDeregistered {
+    pub keeper: Pubkey,
+    pub bond_returned: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a trigger fails the slippage check and the keeper is penalized
+#[event]
+pub struct KeeperSlashed {
+    pub keeper: Pubkey,
+    pub min_amount_out: u64,
+    pub required_min_out: u64,
+    pub slashed: u64,
+    pub cooldown_until: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `observe_price` moves the keeper registry's reference price
+#[event]
+pub struct ReferencePriceObserved {
+    pub spot_price: u128,
+    pub reference_price: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority withdraws slashed keeper bonds
+#[event]
+pub struct SlashedBondsWithdrawn {
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a config change is queued behind the timelock
+#[event]
+pub struct ConfigChangeQueued {
+    pub update: ConfigUpdate,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a queued config change is dropped
+#[event]
+pub struct ConfigChangeCancelled {
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a queued config change is executed
+#[event]
+pub struct ConfigUpdated {
+    pub buy_tax_rate: u16,
+    pub sell_tax_rate: u16,
+    pub transfer_tax_rate: u16,
+    pub buy_shares: TaxShares,
+    pub sell_shares: TaxShares,
+    pub transfer_shares: TaxShares,
+    pub volume_tiers: VolumeTierTable,
+    pub keeper_bounty_bps: u16,
+    pub vesting_duration: i64,
+    pub unstake_cooldown: i64,
+    pub reward_distribution_threshold: u64,
+    pub buyback_threshold: u64,
+    pub liquidity_threshold: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a new authority is proposed
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority changes hands, or is renounced (`new_authority` is the default pubkey)
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when instructions are paused or unpaused
+#[event]
+pub struct PauseUpdated {
+    pub paused: u8,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority sets the guardian
+#[event]
+pub struct GuardianUpdated {
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,348 @@
+use anchor_lang::prelude::*;
+use crate::{
+    MineballState, PendingConfigChange, ConfigUpdate, MineballError, ConfigChangeQueued,
+    ConfigChangeCancelled, ConfigUpdated, AuthorityProposed, AuthorityTransferred, PauseUpdated, GuardianUpdated,
+    constants::*,
+};
+
+#[derive(Accounts)]
+pub struct AdminOnly<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    /// One change at a time; cancel the pending one to queue another
+    #[account(
+        init,
+        payer = authority,
+        space = PendingConfigChange::LEN,
+        seeds = [PENDING_CONFIG_SEED],
+        bump
+    )]
+    pub pending_config_change: Account<'info, PendingConfigChange>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Queue a change to thresholds, shares, tax rates, volume tiers or keeper bounty, executable from `eta`.
+/// The result is validated as a whole, so shares that must move together go in one change.
+pub fn queue_config_change(
+    ctx: Context<QueueConfigChange>,
+    update: ConfigUpdate,
+    eta: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    let earliest = clock.unix_timestamp
+        .checked_add(MIN_CONFIG_DELAY)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(eta >= earliest, MineballError::ConfigDelayTooShort);
+    
+    // Fail now rather than after the delay if the change could never apply
+    let mut preview = MineballState::clone(&ctx.accounts.state);
+    preview.apply_config(&update)?;
+    
+    let pending = &mut ctx.accounts.pending_config_change;
+    pending.update = update;
+    pending.queued_at = clock.unix_timestamp;
+    pending.eta = eta;
+    pending.bump = ctx.bumps.pending_config_change;
+    
+    emit!(ConfigChangeQueued {
+        update,
+        eta,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Config change queued, executable at {}", eta);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResolveConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_CONFIG_SEED],
+        bump = pending_config_change.bump
+    )]
+    pub pending_config_change: Account<'info, PendingConfigChange>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Apply a queued config change once its timelock has passed
+pub fn execute_config_change(ctx: Context<ResolveConfigChange>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pending = &ctx.accounts.pending_config_change;
+    
+    require!(
+        clock.unix_timestamp >= pending.eta,
+        MineballError::ConfigChangeNotReady
+    );
+    
+    let state = &mut ctx.accounts.state;
+    state.apply_config(&pending.update)?;
+    
+    emit!(ConfigUpdated {
+        buy_tax_rate: state.buy_tax_rate,
+        sell_tax_rate: state.sell_tax_rate,
+        transfer_tax_rate: state.transfer_tax_rate,
+        buy_shares: state.buy_shares,
+        sell_shares: state.sell_shares,
+        transfer_shares: state.transfer_shares,
+        volume_tiers: state.volume_tiers,
+        keeper_bounty_bps: state.keeper_bounty_bps,
+        vesting_duration: state.vesting_duration,
+        unstake_cooldown: state.unstake_cooldown,
+        reward_distribution_threshold: state.reward_distribution_threshold,
+        buyback_threshold: state.buyback_threshold,
+        liquidity_threshold: state.liquidity_threshold,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Config change executed");
+    
+    Ok(())
+}
+
+/// Drop a queued config change
+pub fn cancel_config_change(ctx: Context<ResolveConfigChange>) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    emit!(ConfigChangeCancelled {
+        eta: ctx.accounts.pending_config_change.eta,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Config change cancelled");
+    
+    Ok(())
+}
+
+/// Propose a new authority, which takes over once it calls `accept_authority`.
+/// Proposing the default pubkey cancels a pending proposal.
+pub fn propose_authority(ctx: Context<AdminOnly>, new_authority: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    
+    state.pending_authority = new_authority;
+    
+    emit!(AuthorityProposed {
+        authority: state.authority,
+        pending_authority: new_authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Authority proposed: {}", new_authority);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.pending_authority == pending_authority.key() @ MineballError::Unauthorized
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    pub pending_authority: Signer<'info>,
+}
+
+/// Take over as authority after being proposed
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let previous_authority = state.authority;
+    
+    state.authority = ctx.accounts.pending_authority.key();
+    state.pending_authority = Pubkey::default();
+    
+    emit!(AuthorityTransferred {
+        previous_authority,
+        new_authority: state.authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Authority transferred to {}", state.authority);
+    
+    Ok(())
+}
+
+/// Give up the authority for good. No key can sign for the default pubkey, so
+/// every authority-gated instruction is disabled from here on.
+pub fn renounce_authority(ctx: Context<AdminOnly>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let previous_authority = state.authority;
+    
+    state.authority = Pubkey::default();
+    state.pending_authority = Pubkey::default();
+    
+    emit!(AuthorityTransferred {
+        previous_authority,
+        new_authority: Pubkey::default(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Authority renounced");
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = caller.key() == state.authority
+            || caller.key() == state.guardian @ MineballError::Unauthorized
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    /// The authority, or the current guardian handing over or stepping down,
+    /// which keeps the guardian replaceable after the authority is renounced
+    pub caller: Signer<'info>,
+}
+
+/// Set the guardian allowed to pause instructions. The default pubkey removes it.
+pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    
+    state.guardian = guardian;
+    
+    emit!(GuardianUpdated {
+        guardian,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Guardian set to {}", guardian);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = guardian @ MineballError::Unauthorized,
+        constraint = state.guardian != Pubkey::default() @ MineballError::Unauthorized
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    pub guardian: Signer<'info>,
+}
+
+/// Pause the instruction groups in `flags` (`PAUSE_*` bits). Only a pause of an
+/// unpaused protocol starts the clock, so adding groups can't extend an active pause.
+pub fn pause(ctx: Context<Pause>, flags: u8) -> Result<()> {
+    require!(
+        flags != 0 && flags & !PAUSE_ALL == 0,
+        MineballError::InvalidPauseFlags
+    );
+    
+    let clock = Clock::get()?;
+    let state = &mut ctx.accounts.state;
+    
+    if state.paused == 0 {
+        // Without an authority to replace the guardian, make it leave the protocol
+        // running for PAUSE_COOLDOWN after each full pause
+        if state.authority == Pubkey::default() {
+            let ready_at = state.paused_at
+                .checked_add(MAX_PAUSE_DURATION)
+                .and_then(|expires_at| expires_at.checked_add(PAUSE_COOLDOWN))
+                .ok_or(MineballError::ArithmeticOverflow)?;
+            require!(clock.unix_timestamp >= ready_at, MineballError::PauseCooldownActive);
+        }
+        state.paused_at = clock.unix_timestamp;
+    }
+    state.paused |= flags;
+    
+    emit!(PauseUpdated {
+        paused: state.paused,
+        caller: ctx.accounts.guardian.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Paused: {:#07b}", state.paused);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    /// The authority, or anyone once the pause has outlived MAX_PAUSE_DURATION
+    pub caller: Signer<'info>,
+}
+
+/// Unpause the instruction groups in `flags`. The guardian can't unpause on its own.
+pub fn unpause(ctx: Context<Unpause>, flags: u8) -> Result<()> {
+    require!(
+        flags != 0 && flags & !PAUSE_ALL == 0,
+        MineballError::InvalidPauseFlags
+    );
+    
+    let clock = Clock::get()?;
+    let state = &mut ctx.accounts.state;
+    
+    if ctx.accounts.caller.key() != state.authority {
+        let expires_at = state.paused_at
+            .checked_add(MAX_PAUSE_DURATION)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        require!(clock.unix_timestamp >= expires_at, MineballError::Unauthorized);
+    }
+    
+    state.paused &= !flags;
+    
+    emit!(PauseUpdated {
+        paused: state.paused,
+        caller: ctx.accounts.caller.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Paused: {:#07b}", state.paused);
+    
+    Ok(())
+}
@@ -1,69 +1,431 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
-use crate::{MineballState, MineballError, BuybackExecuted, constants::*};
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, KeeperRegistry, Keeper, MineballError, BuybackExecuted, BuybackDeposited,
+    constants::*,
+    instructions::keeper::{penalize_keeper, required_min_out},
+    amm::{self, SwapAccounts},
+    utils::{apply_bps, assert_vault_backed, transfer_tokens},
+};
 
 #[derive(Accounts)]
-pub struct ExecuteBuyback<'info> {
+pub struct InitializeBuybackVault<'info> {
     #[account(
         mut,
         seeds = [STATE_SEED],
-        bump = state.bump
+        bump = state.bump,
+        has_one = authority
     )]
     pub state: Account<'info, MineballState>,
     
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Mint the buyback is paid in (e.g. wrapped SOL)
+    #[account(constraint = quote_mint.key() != state.mint @ MineballError::InvalidQuoteMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = quote_mint,
+        token::authority = state,
+        seeds = [BUYBACK_VAULT_SEED],
+        bump
+    )]
+    pub buyback_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: only stored, every buyback CPI is pinned to this program
+    #[account(executable)]
+    pub amm_program: UncheckedAccount<'info>,
+    
+    /// CHECK: only stored, validated by the AMM on every swap
+    #[account(owner = amm_program.key())]
+    pub amm_pool: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Token program of the quote mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Open the quote vault buybacks are paid from and pin the AMM pool they trade on.
+/// `burn_purchased` decides whether bought tokens, and the buyback share of the tax,
+/// are burned or parked in the buyback pool.
+/// `buyback_threshold` is the vault balance a buyback needs, in units of the quote mint.
+pub fn initialize_buyback_vault(
+    ctx: Context<InitializeBuybackVault>,
+    burn_purchased: bool,
+    buyback_threshold: u64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    
+    state.quote_mint = ctx.accounts.quote_mint.key();
+    state.amm_program = ctx.accounts.amm_program.key();
+    state.amm_pool = ctx.accounts.amm_pool.key();
+    state.burn_buybacks = burn_purchased;
+    state.buyback_threshold = buyback_threshold;
+    
+    msg!("Buyback vault opened for quote mint {}", state.quote_mint);
+    msg!("AMM pool: {}", state.amm_pool);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        has_one = quote_mint @ MineballError::InvalidMint,
+        has_one = amm_program @ MineballError::InvalidAmm,
+        has_one = amm_pool @ MineballError::InvalidAmm,
+        constraint = !state.is_paused(PAUSE_BUYBACK) @ MineballError::ProtocolPaused
+    )]
+    pub state: Box<Account<'info, MineballState>>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_VAULT_SEED],
+        bump
+    )]
+    pub buyback_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
     #[account(
         mut,
         seeds = [BUYBACK_POOL_SEED],
         bump
     )]
-    pub buyback_pool: Account<'info, TokenAccount>,
+    pub buyback_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: pinned to the AMM stored at `initialize_buyback_vault`
+    pub amm_program: UncheckedAccount<'info>,
+    
+    /// CHECK: pinned to the pool stored at `initialize_buyback_vault`
+    pub amm_pool: UncheckedAccount<'info>,
+    
+    /// AMM vault of the quote mint, read for the keeper slippage check
+    #[account(
+        mut,
+        constraint = amm_quote_vault.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = amm_quote_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     
-    /// The account that will execute the buyback (usually authority or automated bot)
+    /// AMM vault of the mineball mint, read for the keeper slippage check
+    #[account(
+        mut,
+        constraint = amm_token_vault.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = amm_token_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Box<Account<'info, KeeperRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, executor.key().as_ref()],
+        bump = keeper_info.bump
+    )]
+    pub keeper_info: Box<Account<'info, Keeper>>,
+    
+    /// Registered keeper submitting the trigger
     pub executor: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    /// Receives the keeper bounty in the quote mint
+    #[account(
+        mut,
+        constraint = executor_quote_account.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = executor_quote_account.owner == executor.key() @ MineballError::InvalidOwner
+    )]
+    pub executor_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn execute_buyback(ctx: Context<ExecuteBuyback>) -> Result<()> {
-    let state = &mut ctx.accounts.state;
+/// Swap the quote vault for mineball on the configured AMM.
+/// Fails if the deadline has passed or fewer than `min_amount_out` tokens arrive; a
+/// `min_amount_out` looser than the keeper registry allows slashes the keeper instead.
+pub fn execute_buyback<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteBuyback<'info>>,
+    min_amount_out: u64,
+    deadline: i64,
+) -> Result<()> {
     let clock = Clock::get()?;
     
-    // Check if buyback pool has reached threshold
+    require!(clock.unix_timestamp <= deadline, MineballError::DeadlineExceeded);
+    require!(
+        clock.unix_timestamp >= ctx.accounts.keeper_info.cooldown_until,
+        MineballError::KeeperInCooldown
+    );
+    
+    // Check if buyback vault has reached threshold
     require!(
-        state.buyback_pool >= state.buyback_threshold,
+        ctx.accounts.state.buyback_ready(ctx.accounts.buyback_vault.amount),
         MineballError::BuybackPoolBelowThreshold
     );
     
-    let buyback_amount = state.buyback_pool;
+    let accounts = ctx.accounts;
+    run_buyback(
+        BuybackAccounts {
+            state: &mut accounts.state,
+            buyback_vault: &accounts.buyback_vault,
+            buyback_pool: &mut accounts.buyback_pool,
+            quote_mint: &accounts.quote_mint,
+            mint: &mut accounts.mint,
+            amm_program: &accounts.amm_program,
+            amm_pool: &accounts.amm_pool,
+            amm_quote_vault: &accounts.amm_quote_vault,
+            amm_token_vault: &accounts.amm_token_vault,
+            keeper_registry: &mut accounts.keeper_registry,
+            keeper_info: &mut accounts.keeper_info,
+            executor: accounts.executor.key(),
+            executor_quote_account: &accounts.executor_quote_account,
+            quote_token_program: &accounts.quote_token_program,
+            token_program: &accounts.token_program,
+        },
+        ctx.remaining_accounts,
+        min_amount_out,
+        clock.unix_timestamp,
+    )?;
+    
+    Ok(())
+}
+
+/// Accounts a buyback moves tokens through, borrowed from `ExecuteBuyback` or `Crank`
+pub struct BuybackAccounts<'a, 'info> {
+    pub state: &'a mut Account<'info, MineballState>,
+    pub buyback_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub buyback_pool: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub quote_mint: &'a InterfaceAccount<'info, Mint>,
+    pub mint: &'a mut InterfaceAccount<'info, Mint>,
+    pub amm_program: &'a AccountInfo<'info>,
+    pub amm_pool: &'a AccountInfo<'info>,
+    pub amm_quote_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub amm_token_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub keeper_registry: &'a mut Account<'info, KeeperRegistry>,
+    pub keeper_info: &'a mut Account<'info, Keeper>,
+    pub executor: Pubkey,
+    pub executor_quote_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub quote_token_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+/// Pay the keeper bounty and swap the rest of the buyback vault.
+/// Returns false when the keeper was penalized instead.
+pub fn run_buyback<'info>(
+    accounts: BuybackAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    min_amount_out: u64,
+    now: i64,
+) -> Result<bool> {
+    // The keeper bounty comes off the top, the rest is swapped
+    let vault_amount = accounts.buyback_vault.amount;
+    let keeper_bounty = apply_bps(vault_amount, accounts.state.keeper_bounty_bps)?;
+    let buyback_amount = vault_amount
+        .checked_sub(keeper_bounty)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    // A trigger looser than the registry allows is penalized instead of executed
+    let required = required_min_out(
+        accounts.keeper_registry,
+        buyback_amount,
+        accounts.amm_quote_vault.amount,
+        true,
+    )?;
+    if min_amount_out < required {
+        penalize_keeper(
+            accounts.keeper_registry,
+            accounts.keeper_info,
+            min_amount_out,
+            required,
+            now,
+        )?;
+        return Ok(false);
+    }
+    
+    let state_seeds = &[STATE_SEED, &[accounts.state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    if keeper_bounty > 0 {
+        transfer_tokens(
+            accounts.quote_token_program,
+            &accounts.buyback_vault.to_account_info(),
+            accounts.quote_mint,
+            &accounts.executor_quote_account.to_account_info(),
+            &accounts.state.to_account_info(),
+            &[],
+            keeper_bounty,
+            signer_seeds,
+        )?;
+    }
     
-    // In a real implementation, this would:
-    // 1. Swap the accumulated tokens for SOL via a DEX
-    // 2. Use the SOL to buy back mineball tokens
-    // 3. Either burn them or add to liquidity
-    //
-    // For this MVP, we simulate the execution
-    // You would integrate with Raydium/Orca/Jupiter here
+    let pool_before = accounts.buyback_pool.amount;
     
-    let tokens_bought = buyback_amount; // Simplified for MVP
+    amm::swap(
+        accounts.amm_program,
+        SwapAccounts {
+            pool: accounts.amm_pool.clone(),
+            authority: accounts.state.to_account_info(),
+            source: accounts.buyback_vault.to_account_info(),
+            destination: accounts.buyback_pool.to_account_info(),
+            vault_in: accounts.amm_quote_vault.to_account_info(),
+            vault_out: accounts.amm_token_vault.to_account_info(),
+            mint_in: accounts.quote_mint.to_account_info(),
+            mint_out: accounts.mint.to_account_info(),
+            token_program_in: accounts.quote_token_program.clone(),
+            token_program_out: accounts.token_program.clone(),
+        },
+        remaining_accounts,
+        buyback_amount,
+        min_amount_out,
+        signer_seeds,
+    )?;
+    
+    // Measure what actually arrived rather than trusting the AMM
+    accounts.buyback_pool.reload()?;
+    let tokens_bought = accounts.buyback_pool.amount
+        .checked_sub(pool_before)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(tokens_bought >= min_amount_out, MineballError::SlippageExceeded);
+    
+    // The buyback share of the tax is retired with the purchase instead of being
+    // traded: both are burned, or both are parked in the buyback pool
+    let tax_share = accounts.state.buyback_pool;
+    let retired = tokens_bought
+        .checked_add(tax_share)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    let tokens_burned = if accounts.state.burn_buybacks && retired > 0 {
+        let burn_ctx = CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            Burn {
+                mint: accounts.mint.to_account_info(),
+                from: accounts.buyback_pool.to_account_info(),
+                authority: accounts.state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::burn(burn_ctx, retired)?;
+        accounts.mint.reload()?;
+        retired
+    } else {
+        0
+    };
+    
+    let state = accounts.state;
     
     // Update state
     state.total_buyback_executed = state.total_buyback_executed
         .checked_add(buyback_amount)
         .ok_or(MineballError::ArithmeticOverflow)?;
     
+    if tokens_burned > 0 {
+        state.total_burned = state.total_burned
+            .checked_add(tokens_burned)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        state.total_supply = accounts.mint.supply;
+    } else {
+        state.bought_back = state.bought_back
+            .checked_add(retired)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    }
     state.buyback_pool = 0;
-    state.last_buyback = clock.unix_timestamp;
+    
+    state.last_buyback = now;
+    
+    let keeper_info = accounts.keeper_info;
+    keeper_info.triggers_executed = keeper_info.triggers_executed.saturating_add(1);
     
     // Emit event
     emit!(BuybackExecuted {
-        cycle: state.total_mining_cycles,
+        cycle: state.current_cycle(),
         amount: buyback_amount,
         tokens_bought,
-        timestamp: clock.unix_timestamp,
+        tax_share,
+        tokens_burned,
+        keeper: accounts.executor,
+        keeper_bounty,
+        timestamp: now,
     });
     
-    msg!("Buyback executed: {} tokens", buyback_amount);
+    msg!("Buyback executed: {} quote tokens", buyback_amount);
     msg!("Tokens bought: {}", tokens_bought);
     
+    assert_vault_backed(accounts.buyback_pool, state.buyback_vault_liability()?)?;
+    
+    Ok(true)
+}
+
+#[derive(Accounts)]
+pub struct DepositBuyback<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = quote_mint @ MineballError::InvalidMint
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_VAULT_SEED],
+        bump
+    )]
+    pub buyback_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    
+    pub depositor: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = depositor_quote_account.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = depositor_quote_account.owner == depositor.key() @ MineballError::InvalidOwner
+    )]
+    pub depositor_quote_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Token program of the quote mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Add quote to the buyback vault, e.g. from a treasury or creator revenue.
+/// Permissionless; `execute_buyback` spends the vault once it reaches `buyback_threshold`.
+pub fn deposit_buyback<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositBuyback<'info>>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, MineballError::InvalidAmount);
+    
+    transfer_tokens(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.depositor_quote_account.to_account_info(),
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.buyback_vault.to_account_info(),
+        &ctx.accounts.depositor.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        &[],
+    )?;
+    
+    emit!(BuybackDeposited {
+        depositor: ctx.accounts.depositor.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Buyback vault funded with {} quote tokens", amount);
+    
     Ok(())
 }
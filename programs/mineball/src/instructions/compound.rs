@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use crate::{
+    MineballState, HolderInfo, MineballError, RewardsCompounded,
+    constants::*,
+};
+
+#[derive(Accounts)]
+pub struct SetCompoundPreference<'info> {
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = HolderInfo::LEN,
+        seeds = [HOLDER_SEED, holder.key().as_ref()],
+        bump
+    )]
+    pub holder_info: Account<'info, HolderInfo>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Opt in or out of restaking claimed rewards.
+/// Opting out leaves the compounded balance earning until the next claim pays it out.
+pub fn set_compound_preference(ctx: Context<SetCompoundPreference>, enabled: bool) -> Result<()> {
+    let holder_info = &mut ctx.accounts.holder_info;
+    
+    holder_info.initialize_if_needed(ctx.accounts.holder.key(), ctx.bumps.holder_info);
+    holder_info.compound = enabled;
+    
+    msg!("Compounding {}", if enabled { "enabled" } else { "disabled" });
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CompoundFor<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = !state.is_paused(PAUSE_CLAIMS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [HOLDER_SEED, holder.key().as_ref()],
+        bump = holder_info.bump,
+        has_one = holder @ MineballError::Unauthorized
+    )]
+    pub holder_info: Account<'info, HolderInfo>,
+    
+    /// CHECK: only used as the owner the PDA is derived from
+    pub holder: UncheckedAccount<'info>,
+    
+    /// Keeper compounding on the holder's behalf
+    pub caller: Signer<'info>,
+}
+
+/// Settle and restake a holder's rewards on their behalf.
+/// Permissionless, but only for holders who opted into compounding.
+pub fn compound_for(ctx: Context<CompoundFor>) -> Result<()> {
+    let holder_info = &mut ctx.accounts.holder_info;
+    let state = &mut ctx.accounts.state;
+    let clock = Clock::get()?;
+    
+    require!(holder_info.compound, MineballError::CompoundingDisabled);
+    
+    let amount = holder_info.compound(state)?;
+    require!(amount > 0, MineballError::NoRewardsToClaim);
+    
+    emit!(RewardsCompounded {
+        holder: ctx.accounts.holder.key(),
+        amount,
+        compounded_balance: holder_info.compounded_balance,
+        caller: ctx.accounts.caller.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Rewards compounded: {} tokens", amount);
+    
+    Ok(())
+}
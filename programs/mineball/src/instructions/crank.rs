@@ -0,0 +1,296 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, MiningCycle, KeeperRegistry, Keeper, MineballError, RewardsDistributed,
+    constants::*,
+    instructions::{
+        buyback::{run_buyback, BuybackAccounts},
+        cycle::finish_cycle,
+        liquidity::{run_add_liquidity, LiquidityAccounts},
+        rewards::{pay_reward_bounty, RewardBountyAccounts},
+    },
+    utils::assert_vault_backed,
+};
+
+/// Slippage floors and deadline of a crank. The floors of skipped triggers are ignored.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct CrankParams {
+    pub min_buyback_out: u64,
+    pub min_quote_out: u64,
+    pub min_lp_out: u64,
+    pub deadline: i64,
+}
+
+#[derive(Accounts)]
+pub struct Crank<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        has_one = quote_mint @ MineballError::InvalidMint,
+        has_one = lp_mint @ MineballError::InvalidLpMint,
+        has_one = amm_program @ MineballError::InvalidAmm,
+        has_one = amm_pool @ MineballError::InvalidAmm
+    )]
+    pub state: Box<Account<'info, MineballState>>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump
+    )]
+    pub reward_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_VAULT_SEED],
+        bump
+    )]
+    pub buyback_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_POOL_SEED],
+        bump
+    )]
+    pub buyback_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED],
+        bump
+    )]
+    pub liquidity_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_QUOTE_VAULT_SEED],
+        bump
+    )]
+    pub liquidity_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [LP_VAULT_SEED],
+        bump
+    )]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: pinned to the AMM stored at `initialize_buyback_vault`
+    pub amm_program: UncheckedAccount<'info>,
+    
+    /// CHECK: pinned to the pool stored at `initialize_buyback_vault`
+    pub amm_pool: UncheckedAccount<'info>,
+    
+    /// AMM vault of the quote mint, read for the keeper slippage check
+    #[account(
+        mut,
+        constraint = amm_quote_vault.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = amm_quote_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// AMM vault of the mineball mint, read for the keeper slippage check
+    #[account(
+        mut,
+        constraint = amm_token_vault.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = amm_token_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Box<Account<'info, KeeperRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, executor.key().as_ref()],
+        bump = keeper_info.bump
+    )]
+    pub keeper_info: Box<Account<'info, Keeper>>,
+    
+    /// Registered keeper submitting the crank, pays for the next cycle's account
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    
+    /// Receives the distribution and liquidity bounties
+    #[account(
+        mut,
+        constraint = executor_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = executor_token_account.owner == executor.key() @ MineballError::InvalidOwner
+    )]
+    pub executor_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Receives the buyback bounty in the quote mint
+    #[account(
+        mut,
+        constraint = executor_quote_account.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = executor_quote_account.owner == executor.key() @ MineballError::InvalidOwner
+    )]
+    pub executor_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Cycle the crank completes, created if it is the first one
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = MiningCycle::LEN,
+        seeds = [MINING_CYCLE_SEED, state.current_cycle().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub current_cycle: Box<Account<'info, MiningCycle>>,
+    
+    #[account(
+        init,
+        payer = executor,
+        space = MiningCycle::LEN,
+        seeds = [MINING_CYCLE_SEED, (state.current_cycle() + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_cycle: Box<Account<'info, MiningCycle>>,
+    
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub lp_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Run every trigger that has crossed its threshold and isn't paused, then complete
+/// the mining cycle. Distributions go to the accumulator, since Merkle snapshots need
+/// the authority. A loose slippage floor penalizes the keeper and skips the triggers
+/// after it, but the cycle is still completed since `next_cycle` has already been created.
+pub fn crank<'info>(
+    ctx: Context<'_, '_, '_, 'info, Crank<'info>>,
+    params: CrankParams,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    require!(clock.unix_timestamp <= params.deadline, MineballError::DeadlineExceeded);
+    require!(
+        clock.unix_timestamp >= ctx.accounts.keeper_info.cooldown_until,
+        MineballError::KeeperInCooldown
+    );
+    
+    let accounts = ctx.accounts;
+    let executor = accounts.executor.key();
+    let mut triggered = false;
+    let mut penalized = false;
+    
+    // Distribution
+    let state = &accounts.state;
+    if !state.is_paused(PAUSE_DISTRIBUTION) && state.rewards_ready() && state.total_reward_weight > 0 {
+        let (keeper_bounty, amount) = pay_reward_bounty(
+            RewardBountyAccounts {
+                state,
+                reward_pool: &accounts.reward_pool,
+                mint: &accounts.mint,
+                caller_token_account: &accounts.executor_token_account,
+                token_program: &accounts.token_program,
+            },
+            ctx.remaining_accounts,
+        )?;
+    
+        let state = &mut accounts.state;
+        state.accrue_rewards(amount)?;
+        state.record_distribution(amount, clock.unix_timestamp)?;
+    
+        emit!(RewardsDistributed {
+            cycle: state.current_cycle(),
+            amount,
+            recipients: state.reward_holders,
+            keeper: executor,
+            keeper_bounty,
+            timestamp: clock.unix_timestamp,
+        });
+    
+        msg!("Rewards distributed: {} tokens", amount);
+        assert_vault_backed(&mut accounts.reward_pool, state.reward_vault_liability()?)?;
+        triggered = true;
+    }
+    
+    // Buyback
+    let state = &accounts.state;
+    if !state.is_paused(PAUSE_BUYBACK) && state.buyback_ready(accounts.buyback_vault.amount) {
+        let executed = run_buyback(
+            BuybackAccounts {
+                state: &mut accounts.state,
+                buyback_vault: &accounts.buyback_vault,
+                buyback_pool: &mut accounts.buyback_pool,
+                quote_mint: &accounts.quote_mint,
+                mint: &mut accounts.mint,
+                amm_program: &accounts.amm_program,
+                amm_pool: &accounts.amm_pool,
+                amm_quote_vault: &accounts.amm_quote_vault,
+                amm_token_vault: &accounts.amm_token_vault,
+                keeper_registry: &mut accounts.keeper_registry,
+                keeper_info: &mut accounts.keeper_info,
+                executor,
+                executor_quote_account: &accounts.executor_quote_account,
+                quote_token_program: &accounts.quote_token_program,
+                token_program: &accounts.token_program,
+            },
+            ctx.remaining_accounts,
+            params.min_buyback_out,
+            clock.unix_timestamp,
+        )?;
+        penalized = !executed;
+        triggered |= executed;
+    }
+    
+    // Liquidity, skipped once the keeper has been penalized
+    let state = &accounts.state;
+    if !penalized && !state.is_paused(PAUSE_LIQUIDITY) && state.liquidity_ready() {
+        let executed = run_add_liquidity(
+            LiquidityAccounts {
+                state: &mut accounts.state,
+                liquidity_pool: &mut accounts.liquidity_pool,
+                liquidity_quote_vault: &mut accounts.liquidity_quote_vault,
+                lp_vault: &mut accounts.lp_vault,
+                mint: &accounts.mint,
+                quote_mint: &accounts.quote_mint,
+                lp_mint: &accounts.lp_mint,
+                amm_program: &accounts.amm_program,
+                amm_pool: &accounts.amm_pool,
+                amm_quote_vault: &accounts.amm_quote_vault,
+                amm_token_vault: &accounts.amm_token_vault,
+                keeper_registry: &mut accounts.keeper_registry,
+                keeper_info: &mut accounts.keeper_info,
+                executor,
+                executor_token_account: &accounts.executor_token_account,
+                quote_token_program: &accounts.quote_token_program,
+                token_program: &accounts.token_program,
+                lp_token_program: &accounts.lp_token_program,
+            },
+            ctx.remaining_accounts,
+            params.min_quote_out,
+            params.min_lp_out,
+            clock.unix_timestamp,
+        )?;
+        penalized = !executed;
+        triggered |= executed;
+    }
+    
+    require!(triggered || penalized, MineballError::NothingToCrank);
+    
+    // Every crank that does work closes the cycle, however short
+    accounts.current_cycle.open_first(&accounts.state, executor, ctx.bumps.current_cycle);
+    finish_cycle(
+        &mut accounts.state,
+        &mut accounts.current_cycle,
+        &mut accounts.next_cycle,
+        ctx.bumps.next_cycle,
+        executor,
+        clock.unix_timestamp,
+    )
+}
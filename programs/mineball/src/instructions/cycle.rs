@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use crate::{
+    MineballState, MiningCycle, MineballError, MiningCycleClosed, MiningCycleCompleted,
+    constants::*,
+};
+
+#[derive(Accounts)]
+pub struct CompleteCycle<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    /// Cycle being completed. Only the first one is created here, every
+    /// later cycle is opened when the one before it completes.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MiningCycle::LEN,
+        seeds = [MINING_CYCLE_SEED, state.current_cycle().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub current_cycle: Account<'info, MiningCycle>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = MiningCycle::LEN,
+        seeds = [MINING_CYCLE_SEED, (state.current_cycle() + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_cycle: Account<'info, MiningCycle>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Close the open mining cycle with its volume, rewards, buyback and liquidity
+/// and open the next one. Permissionless once the cycle has run MIN_CYCLE_DURATION.
+pub fn complete_cycle(ctx: Context<CompleteCycle>) -> Result<()> {
+    let clock = Clock::get()?;
+    let current_cycle = &mut ctx.accounts.current_cycle;
+    
+    current_cycle.open_first(&ctx.accounts.state, ctx.accounts.payer.key(), ctx.bumps.current_cycle);
+    
+    require!(
+        clock.unix_timestamp - current_cycle.started_at >= MIN_CYCLE_DURATION,
+        MineballError::CycleNotComplete
+    );
+    
+    finish_cycle(
+        &mut ctx.accounts.state,
+        current_cycle,
+        &mut ctx.accounts.next_cycle,
+        ctx.bumps.next_cycle,
+        ctx.accounts.payer.key(),
+        clock.unix_timestamp,
+    )
+}
+
+/// Complete `current_cycle` at `now`, open `next_cycle` after it and emit
+/// `MiningCycleCompleted`. Shared by `complete_cycle` and `crank`, where
+/// `keeper` also paid for `next_cycle`.
+pub fn finish_cycle(
+    state: &mut MineballState,
+    current_cycle: &mut MiningCycle,
+    next_cycle: &mut MiningCycle,
+    next_cycle_bump: u8,
+    keeper: Pubkey,
+    now: i64,
+) -> Result<()> {
+    current_cycle.complete(state, now)?;
+    
+    state.total_mining_cycles = current_cycle.cycle;
+    next_cycle.open(state.current_cycle(), state, keeper, now, next_cycle_bump);
+    
+    emit!(MiningCycleCompleted {
+        cycle: current_cycle.cycle,
+        started_at: current_cycle.started_at,
+        volume: current_cycle.volume,
+        rewards_distributed: current_cycle.rewards_distributed,
+        buyback_executed: current_cycle.buyback_executed,
+        liquidity_added: current_cycle.liquidity_added,
+        keeper,
+        timestamp: now,
+    });
+    
+    msg!("Mining cycle {} completed", current_cycle.cycle);
+    msg!("Volume: {}, rewards distributed: {}", current_cycle.volume, current_cycle.rewards_distributed);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(cycle: u64)]
+pub struct CloseCycle<'info> {
+    /// Closed once completed, the rent goes back to whoever paid it
+    #[account(
+        mut,
+        close = payer,
+        seeds = [MINING_CYCLE_SEED, cycle.to_le_bytes().as_ref()],
+        bump = mining_cycle.bump
+    )]
+    pub mining_cycle: Account<'info, MiningCycle>,
+    
+    /// CHECK: only receives the rent, pinned to the cycle's payer
+    #[account(
+        mut,
+        address = mining_cycle.payer @ MineballError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
+}
+
+/// Close a completed mining cycle and refund its rent. Permissionless, since
+/// nothing reads a cycle once it completes and `MiningCycleCompleted` keeps its record.
+pub fn close_cycle(ctx: Context<CloseCycle>, _cycle: u64) -> Result<()> {
+    let mining_cycle = &ctx.accounts.mining_cycle;
+    let clock = Clock::get()?;
+    
+    require!(mining_cycle.completed_at != 0, MineballError::CycleStillOpen);
+    
+    emit!(MiningCycleClosed {
+        cycle: mining_cycle.cycle,
+        payer: mining_cycle.payer,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Mining cycle {} closed", mining_cycle.cycle);
+    
+    Ok(())
+}
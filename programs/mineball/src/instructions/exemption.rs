@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use crate::{
+    MineballState, FeeExemption, MineballError, FeeExemptionSet, FeeExemptionRemoved,
+    constants::*,
+};
+
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct AddFeeExemption<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = FeeExemption::LEN,
+        seeds = [FEE_EXEMPTION_SEED, address.as_ref()],
+        bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Exempt `address` from `discount_bps` of the tax on transfers it sends or receives.
+/// Calling it again for the same address updates the discount.
+pub fn add_fee_exemption(
+    ctx: Context<AddFeeExemption>,
+    address: Pubkey,
+    discount_bps: u16,
+) -> Result<()> {
+    require!(
+        discount_bps > 0 && discount_bps <= BASIS_POINTS,
+        MineballError::InvalidExemption
+    );
+    
+    let clock = Clock::get()?;
+    let fee_exemption = &mut ctx.accounts.fee_exemption;
+    
+    if fee_exemption.address == Pubkey::default() {
+        fee_exemption.address = address;
+        fee_exemption.created_at = clock.unix_timestamp;
+        fee_exemption.bump = ctx.bumps.fee_exemption;
+    }
+    fee_exemption.discount_bps = discount_bps;
+    
+    emit!(FeeExemptionSet {
+        address,
+        discount_bps,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Fee exemption for {}: {}bps", address, discount_bps);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeExemption<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [FEE_EXEMPTION_SEED, fee_exemption.address.as_ref()],
+        bump = fee_exemption.bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Remove a fee exemption, taxing the address in full again
+pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>) -> Result<()> {
+    emit!(FeeExemptionRemoved {
+        address: ctx.accounts.fee_exemption.address,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Fee exemption removed for {}", ctx.accounts.fee_exemption.address);
+    
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_spl::token_interface::{
+    self, Burn, Mint, TokenAccount, TokenInterface,
+    spl_token_2022::extension::transfer_fee::instruction::{
+        harvest_withheld_tokens_to_mint, withdraw_withheld_tokens_from_mint,
+    },
+};
+use crate::{
+    MineballState, MineballError, TaxMode, TransferDirection, FeesHarvested, constants::*,
+    utils::{assert_pools_backed, transfer_tokens},
+};
+
+#[derive(Accounts)]
+pub struct HarvestAndSplit<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_TRANSFERS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump
+    )]
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_POOL_SEED],
+        bump
+    )]
+    pub buyback_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED],
+        bump
+    )]
+    pub liquidity_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Sweep withheld transfer fees into the protocol and split them across the pools.
+///
+/// Holder token accounts to harvest from are passed as remaining accounts.
+/// Everything withheld on the mint is withdrawn into the reward pool, then the
+/// buyback and liquidity shares are forwarded and the burn share is burned.
+pub fn harvest_and_split<'info>(
+    ctx: Context<'_, '_, '_, 'info, HarvestAndSplit<'info>>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.state.tax_mode == TaxMode::TransferFee,
+        MineballError::InvalidTaxMode
+    );
+    
+    let clock = Clock::get()?;
+    
+    let state_seeds = &[STATE_SEED, &[ctx.accounts.state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let reward_pool_info = ctx.accounts.reward_pool.to_account_info();
+    let state_info = ctx.accounts.state.to_account_info();
+    
+    // Move fees withheld in holder accounts onto the mint (permissionless)
+    if !ctx.remaining_accounts.is_empty() {
+        let sources: Vec<&Pubkey> = ctx.remaining_accounts.iter().map(|a| a.key).collect();
+        let harvest_ix =
+            harvest_withheld_tokens_to_mint(token_program.key, mint_info.key, &sources)?;
+    
+        let mut account_infos = vec![mint_info.clone()];
+        account_infos.extend_from_slice(ctx.remaining_accounts);
+        invoke(&harvest_ix, &account_infos)?;
+    }
+    
+    // Withdraw everything withheld on the mint into the reward pool
+    let reward_pool_before = ctx.accounts.reward_pool.amount;
+    let withdraw_ix = withdraw_withheld_tokens_from_mint(
+        token_program.key,
+        mint_info.key,
+        reward_pool_info.key,
+        state_info.key,
+        &[],
+    )?;
+    invoke_signed(
+        &withdraw_ix,
+        &[mint_info.clone(), reward_pool_info.clone(), state_info.clone()],
+        signer_seeds,
+    )?;
+    
+    ctx.accounts.reward_pool.reload()?;
+    let harvested = ctx.accounts.reward_pool.amount
+        .checked_sub(reward_pool_before)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(harvested > 0, MineballError::NothingToHarvest);
+    
+    // The native fee doesn't tell buys from sells, so it's split as transfers
+    let split = ctx.accounts.state.split_tax(harvested, TransferDirection::Transfer)?;
+    
+    // Forward the buyback share. The fee is charged again on this hop and ends
+    // up withheld in the pool, so only the amount actually received is credited.
+    let buyback_received = if split.buyback_amount > 0 {
+        let before = ctx.accounts.buyback_pool.amount;
+        transfer_tokens(
+            &token_program,
+            &reward_pool_info,
+            &ctx.accounts.mint,
+            &ctx.accounts.buyback_pool.to_account_info(),
+            &state_info,
+            &[],
+            split.buyback_amount,
+            signer_seeds,
+        )?;
+        ctx.accounts.buyback_pool.reload()?;
+        ctx.accounts.buyback_pool.amount
+            .checked_sub(before)
+            .ok_or(MineballError::ArithmeticOverflow)?
+    } else {
+        0
+    };
+    
+    // Forward the liquidity share
+    let liquidity_received = if split.liquidity_amount > 0 {
+        let before = ctx.accounts.liquidity_pool.amount;
+        transfer_tokens(
+            &token_program,
+            &reward_pool_info,
+            &ctx.accounts.mint,
+            &ctx.accounts.liquidity_pool.to_account_info(),
+            &state_info,
+            &[],
+            split.liquidity_amount,
+            signer_seeds,
+        )?;
+        ctx.accounts.liquidity_pool.reload()?;
+        ctx.accounts.liquidity_pool.amount
+            .checked_sub(before)
+            .ok_or(MineballError::ArithmeticOverflow)?
+    } else {
+        0
+    };
+    
+    // Burn the burn share straight out of the reward pool
+    if split.burn_amount > 0 {
+        let burn_ctx = CpiContext::new_with_signer(
+            token_program.clone(),
+            Burn {
+                mint: mint_info.clone(),
+                from: reward_pool_info.clone(),
+                authority: state_info.clone(),
+            },
+            signer_seeds,
+        );
+        token_interface::burn(burn_ctx, split.burn_amount)?;
+    }
+    
+    // Keep the recorded supply in step with the mint
+    ctx.accounts.mint.reload()?;
+    
+    let state = &mut ctx.accounts.state;
+    
+    state.total_supply = ctx.accounts.mint.supply;
+    state.mining_reward_pool = state.mining_reward_pool
+        .checked_add(split.mining_reward_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.buyback_pool = state.buyback_pool
+        .checked_add(buyback_received)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.liquidity_pool = state.liquidity_pool
+        .checked_add(liquidity_received)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.total_burned = state.total_burned
+        .checked_add(split.burn_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(FeesHarvested {
+        amount: harvested,
+        mining_reward_amount: split.mining_reward_amount,
+        buyback_amount: buyback_received,
+        liquidity_amount: liquidity_received,
+        burn_amount: split.burn_amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Fees harvested: {} tokens", harvested);
+    
+    assert_pools_backed(
+        state,
+        &mut ctx.accounts.reward_pool,
+        &mut ctx.accounts.buyback_pool,
+        &mut ctx.accounts.liquidity_pool,
+    )
+}
@@ -0,0 +1,405 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self, Burn, Mint, TokenAccount, TokenInterface,
+    spl_token_2022::{
+        self,
+        extension::{transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions},
+    },
+};
+use crate::{
+    MineballState, TaxLedger, TaxSplit, MineballError, TaxMode, TransferDirection, TransferWithTaxExecuted,
+    HookTaxCollected, ExemptTransferExecuted, constants::*,
+    instructions::limits::{check_transfer_limits, check_wallet_limit},
+    utils::{
+        advance_volume_epoch, assert_pools_backed, discounted_tax, exemption_discount,
+        is_registered_pool, transfer_tokens,
+    },
+};
+
+#[derive(Accounts)]
+pub struct InitializeTaxLedger<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = TaxLedger::LEN,
+        seeds = [TAX_LEDGER_SEED, token_account.key().as_ref()],
+        bump
+    )]
+    pub tax_ledger: Account<'info, TaxLedger>,
+    
+    #[account(constraint = token_account.mint == state.mint @ MineballError::InvalidMint)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Open the ledger a token account needs before it can send tokens to a wallet
+/// while the transfer hook is active. Anyone may pay for it. Trades of an account
+/// without one are charged to the pool's ledger, opened by `register_pool`.
+pub fn initialize_tax_ledger(ctx: Context<InitializeTaxLedger>) -> Result<()> {
+    let tax_ledger = &mut ctx.accounts.tax_ledger;
+    
+    tax_ledger.token_account = ctx.accounts.token_account.key();
+    tax_ledger.tax_owed = 0;
+    tax_ledger.owed_split = TaxSplit::default();
+    tax_ledger.total_tax_paid = 0;
+    tax_ledger.bump = ctx.bumps.tax_ledger;
+    
+    msg!("Tax ledger opened for {}", tax_ledger.token_account);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RecordHookTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_TRANSFERS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        constraint = source_token_account.mint == mint.key() @ MineballError::InvalidMint
+    )]
+    pub source_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        constraint = destination_token_account.mint == mint.key() @ MineballError::InvalidMint
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: tax ledger of the source token account, charged on sells and transfers,
+    /// or on buys the buyer has no ledger for. May not exist.
+    #[account(mut, seeds = [TAX_LEDGER_SEED, source_token_account.key().as_ref()], bump)]
+    pub source_tax_ledger: UncheckedAccount<'info>,
+    
+    /// CHECK: tax ledger of the destination token account, charged on buys, or on
+    /// sells the seller has no ledger for. May not exist.
+    #[account(mut, seeds = [TAX_LEDGER_SEED, destination_token_account.key().as_ref()], bump)]
+    pub destination_tax_ledger: UncheckedAccount<'info>,
+    
+    /// CHECK: fee exemption PDA of the source owner, which may not exist
+    #[account(seeds = [FEE_EXEMPTION_SEED, source_token_account.owner.as_ref()], bump)]
+    pub source_exemption: UncheckedAccount<'info>,
+    
+    /// CHECK: fee exemption PDA of the destination owner, which may not exist
+    #[account(seeds = [FEE_EXEMPTION_SEED, destination_token_account.owner.as_ref()], bump)]
+    pub destination_exemption: UncheckedAccount<'info>,
+    
+    /// CHECK: pool registration of the source token account, which may not exist
+    #[account(seeds = [REGISTERED_POOL_SEED, source_token_account.key().as_ref()], bump)]
+    pub source_pool: UncheckedAccount<'info>,
+    
+    /// CHECK: pool registration of the destination token account, which may not exist
+    #[account(seeds = [REGISTERED_POOL_SEED, destination_token_account.key().as_ref()], bump)]
+    pub destination_pool: UncheckedAccount<'info>,
+}
+
+/// Record the tax owed on a Token-2022 transfer.
+/// Only callable from the transfer hook while the transfer is in flight.
+pub fn record_hook_transfer(ctx: Context<RecordHookTransfer>, amount: u64) -> Result<()> {
+    require!(
+        ctx.accounts.state.tax_mode == TaxMode::TransferHook,
+        MineballError::InvalidTaxMode
+    );
+    
+    // The transferring flag is only set by Token-2022 for the duration of the
+    // hook call, which proves the amount comes from a real transfer
+    assert_is_transferring(&ctx.accounts.source_token_account.to_account_info())?;
+    
+    let discount_bps = exemption_discount(&ctx.accounts.source_exemption)?
+        .max(exemption_discount(&ctx.accounts.destination_exemption)?);
+    let from_pool = is_registered_pool(&ctx.accounts.source_pool);
+    let to_pool = is_registered_pool(&ctx.accounts.destination_pool);
+    let direction = TransferDirection::from_pools(from_pool, to_pool);
+    
+    let source = (
+        &ctx.accounts.source_token_account,
+        ctx.accounts.source_tax_ledger.to_account_info(),
+    );
+    let destination = (
+        &ctx.accounts.destination_token_account,
+        ctx.accounts.destination_tax_ledger.to_account_info(),
+    );
+    
+    // Buys are charged to the buyer rather than to the pool's reserves, sells
+    // and transfers to the sender. A trader without a ledger is charged to the
+    // pool's ledger instead, so a DEX route into a fresh account never reverts.
+    let (charged, pool_side) = if direction == TransferDirection::Buy {
+        (destination, source)
+    } else {
+        (source, destination)
+    };
+    let (taxed_account, ledger_info) =
+        if has_tax_ledger(&charged.1) || direction == TransferDirection::Transfer {
+            charged
+        } else {
+            pool_side
+        };
+    require!(has_tax_ledger(&ledger_info), MineballError::TaxLedgerRequired);
+    let mut tax_ledger = TaxLedger::try_deserialize(&mut &ledger_info.try_borrow_data()?[..])?;
+    
+    let clock = Clock::get()?;
+    
+    // The hook skips transfers signed by or into the state PDA, so only pools
+    // are exempt here. set_trading_limits keeps the sell cooldown off in this
+    // mode, so there is no last sell to check against.
+    check_transfer_limits(
+        &ctx.accounts.state,
+        0,
+        amount,
+        direction,
+        from_pool,
+        to_pool,
+        clock.unix_timestamp,
+    )?;
+    check_wallet_limit(&ctx.accounts.state, ctx.accounts.destination_token_account.amount, to_pool)?;
+    
+    let state = &mut ctx.accounts.state;
+    
+    // Roll the volume window first so the transfer pays the tier now in force
+    advance_volume_epoch(state, clock.unix_timestamp);
+    let (volume_tier, _) = state.active_volume_tier(clock.unix_timestamp);
+    let tax_rate = state.effective_tax_rate(direction, clock.unix_timestamp)?;
+    let tax_amount = discounted_tax(amount, tax_rate, discount_bps)?;
+    
+    tax_ledger.tax_owed = tax_ledger.tax_owed
+        .checked_add(tax_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    // Split now, while the direction is known
+    let split = state.split_tax(tax_amount, direction)?;
+    tax_ledger.owed_split = tax_ledger.owed_split.checked_add(&split)?;
+    
+    // The hook runs after balances are updated, so this is the post-transfer
+    // balance. Keeping it above the debt guarantees the tax can be collected.
+    require!(
+        taxed_account.amount >= tax_ledger.tax_owed,
+        MineballError::TaxDebtUncollectable
+    );
+    tax_ledger.try_serialize(&mut &mut ledger_info.try_borrow_mut_data()?[..])?;
+    
+    // Update volume, leaving out fully exempt internal moves
+    if discount_bps < BASIS_POINTS {
+        state.record_volume(amount)?;
+    }
+    
+    if discount_bps > 0 {
+        emit!(ExemptTransferExecuted {
+            from: ctx.accounts.source_token_account.owner,
+            to: ctx.accounts.destination_token_account.owner,
+            amount,
+            tax_amount,
+            tax_rate,
+            direction,
+            volume_tier,
+            discount_bps,
+            timestamp: clock.unix_timestamp,
+        });
+    } else {
+        emit!(TransferWithTaxExecuted {
+            from: ctx.accounts.source_token_account.owner,
+            to: ctx.accounts.destination_token_account.owner,
+            amount,
+            tax_amount,
+            tax_rate,
+            direction,
+            volume_tier,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectHookTax<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_TRANSFERS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ MineballError::InvalidMint
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [TAX_LEDGER_SEED, token_account.key().as_ref()],
+        bump = tax_ledger.bump
+    )]
+    pub tax_ledger: Account<'info, TaxLedger>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump
+    )]
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_POOL_SEED],
+        bump
+    )]
+    pub buyback_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED],
+        bump
+    )]
+    pub liquidity_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pull the tax recorded against a token account into the protocol pools.
+/// The state PDA is the mint's permanent delegate, so anyone can crank this.
+pub fn collect_hook_tax<'info>(
+    ctx: Context<'_, '_, '_, 'info, CollectHookTax<'info>>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.state.tax_mode == TaxMode::TransferHook,
+        MineballError::InvalidTaxMode
+    );
+    
+    let tax_owed = ctx.accounts.tax_ledger.tax_owed;
+    require!(tax_owed > 0, MineballError::NoTaxOwed);
+    
+    // Burning tokens skips the hook, so the balance can drop below the debt.
+    // Collect what is left and write off the rest rather than leave it stuck.
+    let tax_amount = tax_owed.min(ctx.accounts.token_account.amount);
+    let written_off = tax_owed - tax_amount;
+    
+    let clock = Clock::get()?;
+    let split = ctx.accounts.tax_ledger.owed_split.prorate(tax_amount, tax_owed)?;
+    
+    let state_seeds = &[STATE_SEED, &[ctx.accounts.state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let token_account = ctx.accounts.token_account.to_account_info();
+    let state_info = ctx.accounts.state.to_account_info();
+    
+    let pool_transfers = [
+        (ctx.accounts.reward_pool.to_account_info(), split.mining_reward_amount),
+        (ctx.accounts.buyback_pool.to_account_info(), split.buyback_amount),
+        (ctx.accounts.liquidity_pool.to_account_info(), split.liquidity_amount),
+    ];
+    
+    for (pool, amount) in &pool_transfers {
+        if *amount > 0 {
+            transfer_tokens(
+                &token_program,
+                &token_account,
+                &ctx.accounts.mint,
+                pool,
+                &state_info,
+                ctx.remaining_accounts,
+                *amount,
+                signer_seeds,
+            )?;
+        }
+    }
+    
+    // Burns never invoke the transfer hook, so no extra accounts are needed
+    if split.burn_amount > 0 {
+        let burn_ctx = CpiContext::new_with_signer(
+            token_program.clone(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: token_account.clone(),
+                authority: state_info.clone(),
+            },
+            signer_seeds,
+        );
+        token_interface::burn(burn_ctx, split.burn_amount)?;
+    }
+    
+    // Keep the recorded supply in step with the mint
+    ctx.accounts.mint.reload()?;
+    
+    let state = &mut ctx.accounts.state;
+    
+    state.total_supply = ctx.accounts.mint.supply;
+    state.mining_reward_pool = state.mining_reward_pool
+        .checked_add(split.mining_reward_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.buyback_pool = state.buyback_pool
+        .checked_add(split.buyback_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.liquidity_pool = state.liquidity_pool
+        .checked_add(split.liquidity_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.total_burned = state.total_burned
+        .checked_add(split.burn_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    let tax_ledger = &mut ctx.accounts.tax_ledger;
+    tax_ledger.tax_owed = 0;
+    tax_ledger.owed_split = TaxSplit::default();
+    tax_ledger.total_tax_paid = tax_ledger.total_tax_paid
+        .checked_add(tax_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(HookTaxCollected {
+        token_account: ctx.accounts.token_account.key(),
+        amount: tax_amount,
+        burned: split.burn_amount,
+        written_off,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Hook tax collected: {} tokens, {} written off", tax_amount, written_off);
+    
+    assert_pools_backed(
+        &ctx.accounts.state,
+        &mut ctx.accounts.reward_pool,
+        &mut ctx.accounts.buyback_pool,
+        &mut ctx.accounts.liquidity_pool,
+    )
+}
+
+/// Whether `ledger` is an initialized tax ledger
+fn has_tax_ledger(ledger: &AccountInfo) -> bool {
+    ledger.owner == &crate::ID && !ledger.data_is_empty()
+}
+
+/// Fail unless Token-2022 is currently executing a transfer out of `source`
+fn assert_is_transferring(source: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*source.owner, spl_token_2022::ID, MineballError::NotTransferring);
+    
+    let data = source.try_borrow_data()?;
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    let extension = account
+        .get_extension::<TransferHookAccount>()
+        .map_err(|_| MineballError::NotTransferring)?;
+    
+    require!(bool::from(extension.transferring), MineballError::NotTransferring);
+    
+    Ok(())
+}
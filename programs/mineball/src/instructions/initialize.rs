@@ -1,6 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::{MineballState, MineballError, constants::*, errors::*};
+use anchor_spl::token_interface::{
+    spl_token_2022::{
+        self,
+        extension::{
+            permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig, transfer_hook,
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+    },
+    Mint, TokenAccount, TokenInterface,
+};
+use crate::{
+    MineballState, MineballError, TaxMode, TaxShares, LaunchTaxSchedule, VolumeTierTable,
+    constants::*,
+};
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -16,7 +28,7 @@ pub struct Initialize<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
-    pub mint: Account<'info, Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
     
     #[account(
         init,
@@ -26,7 +38,7 @@ pub struct Initialize<'info> {
         seeds = [REWARD_POOL_SEED],
         bump
     )]
-    pub reward_pool: Account<'info, TokenAccount>,
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         init,
@@ -36,7 +48,7 @@ pub struct Initialize<'info> {
         seeds = [BUYBACK_POOL_SEED],
         bump
     )]
-    pub buyback_pool: Account<'info, TokenAccount>,
+    pub buyback_pool: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         init,
@@ -46,50 +58,127 @@ pub struct Initialize<'info> {
         seeds = [LIQUIDITY_POOL_SEED],
         bump
     )]
-    pub liquidity_pool: Account<'info, TokenAccount>,
+    pub liquidity_pool: InterfaceAccount<'info, TokenAccount>,
     
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+/// Tax rates in basis points for buys, sells and other transfers, and the
+/// launch tax they decay from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct TaxRates {
+    pub buy_tax_rate: u16,
+    pub sell_tax_rate: u16,
+    pub transfer_tax_rate: u16,
+    pub launch_tax: LaunchTaxSchedule,
+}
+
+/// Seconds claimed rewards vest over and unstaked tokens wait before they can be
+/// withdrawn, 0 paying out at once. Later changes go through `queue_config_change`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct HolderTerms {
+    pub vesting_duration: i64,
+    pub unstake_cooldown: i64,
+}
+
 pub fn initialize(
     ctx: Context<Initialize>,
-    tax_rate: u16,
-    mining_reward_share: u16,
-    buyback_share: u16,
-    liquidity_share: u16,
-    burn_share: u16,
+    tax_rates: TaxRates,
+    buy_shares: TaxShares,
+    sell_shares: TaxShares,
+    transfer_shares: TaxShares,
+    keeper_bounty_bps: u16,
+    holder_terms: HolderTerms,
 ) -> Result<()> {
-    // Validate tax rate
-    require!(tax_rate <= MAX_TAX_RATE, MineballError::InvalidTaxRate);
+    let TaxRates { buy_tax_rate, sell_tax_rate, transfer_tax_rate, launch_tax } = tax_rates;
+    let HolderTerms { vesting_duration, unstake_cooldown } = holder_terms;
+    
+    // Validate tax rates
+    require!(
+        buy_tax_rate <= MAX_TAX_RATE
+            && sell_tax_rate <= MAX_TAX_RATE
+            && transfer_tax_rate <= MAX_TAX_RATE,
+        MineballError::InvalidTaxRate
+    );
+    launch_tax.validate()?;
+    require!(
+        keeper_bounty_bps <= MAX_KEEPER_BOUNTY_BPS,
+        MineballError::InvalidKeeperBounty
+    );
+    require!(
+        (0..=MAX_VESTING_DURATION).contains(&vesting_duration),
+        MineballError::InvalidVestingDuration
+    );
+    require!(
+        (0..=MAX_UNSTAKE_COOLDOWN).contains(&unstake_cooldown),
+        MineballError::InvalidUnstakeCooldown
+    );
     
-    // Validate that shares sum to 100%
-    let total_shares = mining_reward_share
-        .checked_add(buyback_share)
-        .and_then(|s| s.checked_add(liquidity_share))
-        .and_then(|s| s.checked_add(burn_share))
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    // Validate that each direction's shares sum to 100%
+    buy_shares.validate()?;
+    sell_shares.validate()?;
+    transfer_shares.validate()?;
     
-    require!(total_shares == BASIS_POINTS, MineballError::InvalidTaxShares);
+    let tax_mode = resolve_tax_mode(
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.state.key(),
+        [buy_tax_rate, sell_tax_rate, transfer_tax_rate],
+    )?;
+    
+    // A native transfer fee can't follow the schedule
+    require!(
+        tax_mode != TaxMode::TransferFee || !launch_tax.is_enabled(),
+        MineballError::InvalidTaxMode
+    );
     
     let state = &mut ctx.accounts.state;
     let clock = Clock::get()?;
     
     state.authority = ctx.accounts.authority.key();
+    state.pending_authority = Pubkey::default();
+    
+    // No guardian until the authority sets one
+    state.guardian = Pubkey::default();
+    state.paused = 0;
+    state.paused_at = 0;
     state.mint = ctx.accounts.mint.key();
     state.total_supply = ctx.accounts.mint.supply;
-    state.tax_rate = tax_rate;
-    state.mining_reward_share = mining_reward_share;
-    state.buyback_share = buyback_share;
-    state.liquidity_share = liquidity_share;
-    state.burn_share = burn_share;
+    state.tax_mode = tax_mode;
+    state.buy_tax_rate = buy_tax_rate;
+    state.sell_tax_rate = sell_tax_rate;
+    state.transfer_tax_rate = transfer_tax_rate;
+    state.launch_tax = launch_tax;
+    
+    // Trading limits are set separately by `set_trading_limits`
+    state.max_tx_amount = 0;
+    state.max_wallet_balance = 0;
+    state.min_seconds_between_sells = 0;
+    state.limits_lifted = false;
+    
+    // Volume tiers start empty and are added through `queue_config_change`
+    state.volume_epoch_start = clock.unix_timestamp;
+    state.epoch_volume = 0;
+    state.previous_epoch_volume = 0;
+    state.volume_tiers = VolumeTierTable::default();
+    state.buy_shares = buy_shares;
+    state.sell_shares = sell_shares;
+    state.transfer_shares = transfer_shares;
+    state.keeper_bounty_bps = keeper_bounty_bps;
+    
+    state.vesting_duration = vesting_duration;
+    state.unstake_cooldown = unstake_cooldown;
     
     // Initialize pools to zero
     state.mining_reward_pool = 0;
     state.buyback_pool = 0;
     state.liquidity_pool = 0;
     state.total_burned = 0;
+    state.unclaimed_rewards = 0;
+    state.vesting_rewards = 0;
+    state.compounded_rewards = 0;
+    state.total_staked = 0;
     
     // Initialize cycle tracking
     state.total_mining_cycles = 0;
@@ -97,10 +186,21 @@ pub fn initialize(
     state.total_rewards_distributed = 0;
     state.total_buyback_executed = 0;
     state.total_liquidity_added = 0;
+    state.acc_reward_per_share = 0;
+    state.total_reward_weight = 0;
+    state.reward_holders = 0;
+    
+    // Buyback venue is configured separately by `initialize_buyback_vault`
+    state.quote_mint = Pubkey::default();
+    state.amm_program = Pubkey::default();
+    state.amm_pool = Pubkey::default();
+    state.burn_buybacks = false;
+    state.lp_mint = Pubkey::default();
     
     // Set thresholds
     state.reward_distribution_threshold = DEFAULT_REWARD_THRESHOLD;
-    state.buyback_threshold = DEFAULT_BUYBACK_THRESHOLD;
+    // In quote units, set once the quote mint is known by `initialize_buyback_vault`
+    state.buyback_threshold = 0;
     state.liquidity_threshold = DEFAULT_LIQUIDITY_THRESHOLD;
     
     // Set timestamps
@@ -112,11 +212,79 @@ pub fn initialize(
     state.bump = ctx.bumps.state;
     
     msg!("mineball protocol initialized");
-    msg!("Tax rate: {}bps", tax_rate);
-    msg!("Mining reward share: {}bps", mining_reward_share);
-    msg!("Buyback share: {}bps", buyback_share);
-    msg!("Liquidity share: {}bps", liquidity_share);
-    msg!("Burn share: {}bps", burn_share);
+    msg!("Tax mode: {:?}", tax_mode);
+    msg!("Tax rates: buy {}bps, sell {}bps, transfer {}bps", buy_tax_rate, sell_tax_rate, transfer_tax_rate);
+    if launch_tax.is_enabled() {
+        msg!("Launch tax: {}bps over {}s", launch_tax.start_rate, launch_tax.duration);
+    }
+    msg!("Buy shares: {:?}", buy_shares);
+    msg!("Sell shares: {:?}", sell_shares);
+    msg!("Transfer shares: {:?}", transfer_shares);
+    msg!("Keeper bounty: {}bps", keeper_bounty_bps);
+    msg!("Vesting: {}s, unstake cooldown: {}s", vesting_duration, unstake_cooldown);
     
     Ok(())
 }
+
+/// Work out how the tax is collected from the mint's extensions.
+///
+/// A Token-2022 mint that registers the mineball transfer hook must also make
+/// the state PDA its permanent delegate, otherwise recorded tax could never be
+/// collected. A mint with a native transfer fee charges every transfer alike, so
+/// all three tax rates must equal its fee, and it must let
+/// the state PDA withdraw the withheld fees. Any other mint falls back to
+/// `transfer_with_tax`.
+fn resolve_tax_mode(
+    mint_info: &AccountInfo,
+    state_key: &Pubkey,
+    tax_rates: [u16; 3],
+) -> Result<TaxMode> {
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(TaxMode::Instruction);
+    }
+    
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    
+    let hook_program_id = transfer_hook::get_program_id(&mint);
+    let fee_config = mint.get_extension::<TransferFeeConfig>().ok();
+    
+    match (hook_program_id, fee_config) {
+        (None, None) => Ok(TaxMode::Instruction),
+        (Some(program_id), None) => {
+            require_keys_eq!(
+                program_id,
+                TRANSFER_HOOK_PROGRAM_ID,
+                MineballError::InvalidMintExtensions
+            );
+    
+            let delegate = mint
+                .get_extension::<PermanentDelegate>()
+                .map_err(|_| MineballError::InvalidMintExtensions)?;
+            require!(
+                Option::<Pubkey>::from(delegate.delegate) == Some(*state_key),
+                MineballError::InvalidMintExtensions
+            );
+    
+            Ok(TaxMode::TransferHook)
+        }
+        (None, Some(fee_config)) => {
+            require!(
+                Option::<Pubkey>::from(fee_config.withdraw_withheld_authority) == Some(*state_key),
+                MineballError::InvalidMintExtensions
+            );
+    
+            let epoch = Clock::get()?.epoch;
+            let fee_basis_points =
+                u16::from(fee_config.get_epoch_fee(epoch).transfer_fee_basis_points);
+            require!(
+                tax_rates.iter().all(|&rate| rate == fee_basis_points),
+                MineballError::InvalidTaxRate
+            );
+    
+            Ok(TaxMode::TransferFee)
+        }
+        // Both would charge every transfer twice
+        (Some(_), Some(_)) => err!(MineballError::InvalidMintExtensions),
+    }
+}
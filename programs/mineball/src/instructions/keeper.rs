@@ -0,0 +1,434 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::{
+    MineballState, KeeperRegistry, Keeper, MineballError, KeeperRegistered,
+    KeeperDeregistered, KeeperSlashed, ReferencePriceObserved, SlashedBondsWithdrawn, constants::*,
+    utils::{apply_bps, constant_product_out},
+};
+
+/// Keeper registry settings
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct KeeperConfig {
+    pub permissioned: bool,
+    pub min_bond: u64,
+    pub max_slippage_bps: u16,
+    pub slash_bps: u16,
+    pub cooldown_seconds: i64,
+    /// Starting reference price, quote per token scaled by PRICE_PRECISION
+    pub reference_price: u128,
+}
+
+#[derive(Accounts)]
+pub struct InitializeKeeperRegistry<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = KeeperRegistry::LEN,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the keeper registry. Once it exists only registered keepers can fire
+/// `execute_buyback` and `add_liquidity`.
+pub fn initialize_keeper_registry(
+    ctx: Context<InitializeKeeperRegistry>,
+    config: KeeperConfig,
+) -> Result<()> {
+    require!(
+        config.max_slippage_bps <= BASIS_POINTS
+            && config.slash_bps <= BASIS_POINTS
+            && config.cooldown_seconds >= 0
+            && config.reference_price > 0,
+        MineballError::InvalidKeeperConfig
+    );
+    
+    let registry = &mut ctx.accounts.keeper_registry;
+    
+    registry.permissioned = config.permissioned;
+    registry.min_bond = config.min_bond;
+    registry.max_slippage_bps = config.max_slippage_bps;
+    registry.slash_bps = config.slash_bps;
+    registry.cooldown_seconds = config.cooldown_seconds;
+    registry.keeper_count = 0;
+    registry.total_slashed = 0;
+    registry.bump = ctx.bumps.keeper_registry;
+    registry.reference_price = config.reference_price;
+    registry.price_observed_at = Clock::get()?.unix_timestamp;
+    
+    msg!("Keeper registry initialized");
+    msg!("Permissioned: {}, min bond: {} lamports", registry.permissioned, registry.min_bond);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterKeeper<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+    
+    #[account(
+        init,
+        payer = keeper,
+        space = Keeper::LEN,
+        seeds = [KEEPER_SEED, keeper.key().as_ref()],
+        bump
+    )]
+    pub keeper_info: Account<'info, Keeper>,
+    
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    
+    /// Required when the registry is permissioned
+    #[account(address = state.authority @ MineballError::Unauthorized)]
+    pub authority: Option<Signer<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Register the signer as a keeper, posting `bond` lamports into its keeper account
+pub fn register_keeper(ctx: Context<RegisterKeeper>, bond: u64) -> Result<()> {
+    let registry = &ctx.accounts.keeper_registry;
+    
+    if registry.permissioned {
+        require!(ctx.accounts.authority.is_some(), MineballError::Unauthorized);
+    }
+    require!(bond >= registry.min_bond, MineballError::InsufficientKeeperBond);
+    
+    if bond > 0 {
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.keeper.to_account_info(),
+                to: ctx.accounts.keeper_info.to_account_info(),
+            },
+        );
+        system_program::transfer(transfer_ctx, bond)?;
+    }
+    
+    let clock = Clock::get()?;
+    
+    let keeper_info = &mut ctx.accounts.keeper_info;
+    keeper_info.keeper = ctx.accounts.keeper.key();
+    keeper_info.bond = bond;
+    keeper_info.registered_at = clock.unix_timestamp;
+    keeper_info.cooldown_until = 0;
+    keeper_info.triggers_executed = 0;
+    keeper_info.slash_count = 0;
+    keeper_info.bump = ctx.bumps.keeper_info;
+    
+    let registry = &mut ctx.accounts.keeper_registry;
+    registry.keeper_count = registry.keeper_count
+        .checked_add(1)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(KeeperRegistered {
+        keeper: keeper_info.keeper,
+        bond,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Keeper registered: {}", keeper_info.keeper);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeregisterKeeper<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+    
+    #[account(
+        mut,
+        close = keeper,
+        seeds = [KEEPER_SEED, keeper.key().as_ref()],
+        bump = keeper_info.bump
+    )]
+    pub keeper_info: Account<'info, Keeper>,
+    
+    /// CHECK: bound to the keeper account by its seeds, receives the bond and rent
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+    
+    /// The keeper itself, or the authority removing it
+    #[account(
+        constraint = caller.key() == keeper.key() || caller.key() == state.authority
+            @ MineballError::Unauthorized
+    )]
+    pub caller: Signer<'info>,
+}
+
+/// Remove a keeper and return its remaining bond.
+/// A keeper can't leave on its own while in cooldown; the authority can always remove it.
+pub fn deregister_keeper(ctx: Context<DeregisterKeeper>) -> Result<()> {
+    let clock = Clock::get()?;
+    let keeper_info = &ctx.accounts.keeper_info;
+    
+    if ctx.accounts.caller.key() != ctx.accounts.state.authority {
+        require!(
+            clock.unix_timestamp >= keeper_info.cooldown_until,
+            MineballError::KeeperInCooldown
+        );
+    }
+    
+    let registry = &mut ctx.accounts.keeper_registry;
+    registry.keeper_count = registry.keeper_count
+        .checked_sub(1)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(KeeperDeregistered {
+        keeper: keeper_info.keeper,
+        bond_returned: keeper_info.bond,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Keeper deregistered: {}", keeper_info.keeper);
+    
+    Ok(())
+}
+
+/// Lowest `min_amount_out` the registry accepts for swapping `amount_in` of the quote
+/// mint (`quote_in`) or of the token. The output side is priced at the registry's
+/// reference price rather than the AMM's spot reserves, which the trigger's own
+/// transaction could have moved; only the depth of `reserve_in` is taken from the
+/// AMM, to allow for price impact.
+pub fn required_min_out(
+    registry: &KeeperRegistry,
+    amount_in: u64,
+    reserve_in: u64,
+    quote_in: bool,
+) -> Result<u64> {
+    let price = registry.reference_price;
+    let reserve_out = if quote_in {
+        (reserve_in as u128)
+            .checked_mul(PRICE_PRECISION)
+            .and_then(|v| v.checked_div(price))
+    } else {
+        (reserve_in as u128)
+            .checked_mul(price)
+            .map(|v| v / PRICE_PRECISION)
+    };
+    let reserve_out = reserve_out
+        .map(|v| v.min(u64::MAX as u128) as u64)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    let expected_out = constant_product_out(amount_in, reserve_in, reserve_out)?;
+    let tolerance = apply_bps(expected_out, registry.max_slippage_bps)?;
+    
+    Ok(expected_out - tolerance)
+}
+
+#[derive(Accounts)]
+pub struct ObservePrice<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        has_one = quote_mint @ MineballError::InvalidMint,
+        has_one = amm_pool @ MineballError::InvalidAmm
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: pinned to the pool stored at `initialize_buyback_vault`
+    pub amm_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        constraint = amm_quote_vault.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = amm_quote_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_quote_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = amm_token_vault.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = amm_token_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_token_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Move the reference price PRICE_SMOOTHING_BPS of the way towards the AMM's spot
+/// price. Permissionless, at most once per PRICE_OBSERVATION_INTERVAL, so pushing
+/// the reference far takes a manipulation sustained over many intervals.
+pub fn observe_price(ctx: Context<ObservePrice>) -> Result<()> {
+    let clock = Clock::get()?;
+    let registry = &mut ctx.accounts.keeper_registry;
+    
+    let ready_at = registry.price_observed_at
+        .checked_add(PRICE_OBSERVATION_INTERVAL)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(clock.unix_timestamp >= ready_at, MineballError::PriceObservationTooSoon);
+    
+    let token_reserve = ctx.accounts.amm_token_vault.amount;
+    require!(token_reserve > 0, MineballError::InvalidAmm);
+    let spot_price = (ctx.accounts.amm_quote_vault.amount as u128)
+        .checked_mul(PRICE_PRECISION)
+        .ok_or(MineballError::ArithmeticOverflow)?
+        / token_reserve as u128;
+    
+    let reference = registry.reference_price;
+    let step = |gap: u128| gap
+        .checked_mul(PRICE_SMOOTHING_BPS as u128)
+        .map(|v| v / BASIS_POINTS as u128)
+        .ok_or(MineballError::ArithmeticOverflow);
+    registry.reference_price = if spot_price >= reference {
+        reference + step(spot_price - reference)?
+    } else {
+        reference - step(reference - spot_price)?
+    };
+    registry.price_observed_at = clock.unix_timestamp;
+    
+    emit!(ReferencePriceObserved {
+        spot_price,
+        reference_price: registry.reference_price,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Reference price {} (spot {})", registry.reference_price, spot_price);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSlashedBonds<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Account<'info, KeeperRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Withdraw the slashed bonds held by the registry, everything above its rent-exempt
+/// minimum, to the authority
+pub fn withdraw_slashed_bonds(ctx: Context<WithdrawSlashedBonds>) -> Result<()> {
+    let registry_account = ctx.accounts.keeper_registry.to_account_info();
+    let authority_account = ctx.accounts.authority.to_account_info();
+    
+    let rent_minimum = Rent::get()?.minimum_balance(KeeperRegistry::LEN);
+    let amount = registry_account.lamports().saturating_sub(rent_minimum);
+    require!(amount > 0, MineballError::NoSlashedBonds);
+    
+    let authority_lamports = authority_account.lamports()
+        .checked_add(amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    **registry_account.try_borrow_mut_lamports()? = rent_minimum;
+    **authority_account.try_borrow_mut_lamports()? = authority_lamports;
+    
+    emit!(SlashedBondsWithdrawn {
+        amount,
+        recipient: authority_account.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Withdrew {} slashed lamports", amount);
+    
+    Ok(())
+}
+
+/// Slash a keeper's bond into the registry and put it in cooldown.
+/// Callers return `Ok` afterwards so the penalty is not rolled back with the trigger.
+pub fn penalize_keeper<'info>(
+    registry: &mut Account<'info, KeeperRegistry>,
+    keeper: &mut Account<'info, Keeper>,
+    min_amount_out: u64,
+    required_min_out: u64,
+    now: i64,
+) -> Result<()> {
+    let slashed = apply_bps(keeper.bond, registry.slash_bps)?;
+    
+    if slashed > 0 {
+        let keeper_account = keeper.to_account_info();
+        let registry_account = registry.to_account_info();
+    
+        let keeper_lamports = keeper_account.lamports()
+            .checked_sub(slashed)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        let registry_lamports = registry_account.lamports()
+            .checked_add(slashed)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        **keeper_account.try_borrow_mut_lamports()? = keeper_lamports;
+        **registry_account.try_borrow_mut_lamports()? = registry_lamports;
+    }
+    
+    keeper.bond = keeper.bond
+        .checked_sub(slashed)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    keeper.cooldown_until = now
+        .checked_add(registry.cooldown_seconds)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    keeper.slash_count = keeper.slash_count.saturating_add(1);
+    
+    registry.total_slashed = registry.total_slashed
+        .checked_add(slashed)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(KeeperSlashed {
+        keeper: keeper.keeper,
+        min_amount_out,
+        required_min_out,
+        slashed,
+        cooldown_until: keeper.cooldown_until,
+        timestamp: now,
+    });
+    
+    msg!("Trigger rejected: min out {} below {}", min_amount_out, required_min_out);
+    msg!("Keeper slashed {} lamports, cooldown until {}", slashed, keeper.cooldown_until);
+    
+    Ok(())
+}
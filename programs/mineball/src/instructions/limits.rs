@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use crate::{
+    AdminOnly, MineballState, MineballError, TaxMode, TransferDirection,
+    TradingLimitsUpdated, TradingLimitsRemoved, constants::*, utils::apply_bps,
+};
+
+/// Set the launch trading limits, 0 disables a limit. Limits can't be set below
+/// `MIN_TRADING_LIMIT_BPS` of supply or a cooldown above `MAX_SELL_COOLDOWN`,
+/// so they can't be used to freeze holders. Only limits the tax mode can enforce
+/// are accepted.
+pub fn set_trading_limits(
+    ctx: Context<AdminOnly>,
+    max_tx_amount: u64,
+    max_wallet_balance: u64,
+    min_seconds_between_sells: i64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(!state.limits_lifted, MineballError::TradingLimitsLifted);
+    
+    // Transfer-fee mints move without calling mineball at all, and the hook
+    // has no holder info to track a wallet's last sell in
+    require!(
+        state.tax_mode == TaxMode::Instruction
+            || (state.tax_mode == TaxMode::TransferHook && min_seconds_between_sells == 0),
+        MineballError::InvalidTaxMode
+    );
+    
+    let min_limit = apply_bps(state.total_supply, MIN_TRADING_LIMIT_BPS)?;
+    require!(
+        (max_tx_amount == 0 || max_tx_amount >= min_limit)
+            && (max_wallet_balance == 0 || max_wallet_balance >= min_limit)
+            && (0..=MAX_SELL_COOLDOWN).contains(&min_seconds_between_sells),
+        MineballError::InvalidTradingLimits
+    );
+    
+    state.max_tx_amount = max_tx_amount;
+    state.max_wallet_balance = max_wallet_balance;
+    state.min_seconds_between_sells = min_seconds_between_sells;
+    
+    emit!(TradingLimitsUpdated {
+        max_tx_amount,
+        max_wallet_balance,
+        min_seconds_between_sells,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Trading limits: max tx {}, max wallet {}", max_tx_amount, max_wallet_balance);
+    msg!("Sell cooldown: {}s", min_seconds_between_sells);
+    
+    Ok(())
+}
+
+/// Lift the trading limits for good once launch is over
+pub fn lift_trading_limits(ctx: Context<AdminOnly>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require!(!state.limits_lifted, MineballError::TradingLimitsLifted);
+    
+    state.max_tx_amount = 0;
+    state.max_wallet_balance = 0;
+    state.min_seconds_between_sells = 0;
+    state.limits_lifted = true;
+    
+    emit!(TradingLimitsRemoved {
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Trading limits lifted");
+    
+    Ok(())
+}
+
+/// Max-tx and sell-cooldown checks, run before a transfer moves any tokens.
+/// Pools and protocol vaults are exempt, so a move between two of them is never limited.
+pub fn check_transfer_limits(
+    state: &MineballState,
+    last_sell_at: i64,
+    amount: u64,
+    direction: TransferDirection,
+    from_unlimited: bool,
+    to_unlimited: bool,
+    now: i64,
+) -> Result<()> {
+    if state.limits_lifted || (from_unlimited && to_unlimited) {
+        return Ok(());
+    }
+    
+    if state.max_tx_amount > 0 {
+        require!(amount <= state.max_tx_amount, MineballError::MaxTransactionExceeded);
+    }
+    
+    if direction == TransferDirection::Sell && state.min_seconds_between_sells > 0 {
+        let ready_at = last_sell_at
+            .checked_add(state.min_seconds_between_sells)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        require!(now >= ready_at, MineballError::SellCooldownActive);
+    }
+    
+    Ok(())
+}
+
+/// Max-wallet check on the recipient's balance after the transfer
+pub fn check_wallet_limit(state: &MineballState, balance: u64, to_unlimited: bool) -> Result<()> {
+    if state.limits_lifted || to_unlimited || state.max_wallet_balance == 0 {
+        return Ok(());
+    }
+    
+    require!(balance <= state.max_wallet_balance, MineballError::MaxWalletExceeded);
+    
+    Ok(())
+}
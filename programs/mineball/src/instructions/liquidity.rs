@@ -1,65 +1,413 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
-use crate::{MineballState, MineballError, LiquidityAdded, constants::*};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, KeeperRegistry, Keeper, MineballError, LiquidityAdded, constants::*,
+    instructions::keeper::{penalize_keeper, required_min_out},
+    amm::{self, DepositAccounts, SwapAccounts},
+    utils::{apply_bps, assert_vault_backed, transfer_tokens},
+};
 
 #[derive(Accounts)]
-pub struct AddLiquidity<'info> {
+pub struct InitializeLpVault<'info> {
     #[account(
         mut,
         seeds = [STATE_SEED],
-        bump = state.bump
+        bump = state.bump,
+        has_one = authority,
+        has_one = quote_mint @ MineballError::InvalidMint
     )]
     pub state: Account<'info, MineballState>,
     
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    
+    /// LP mint of the pinned AMM pool
+    #[account(
+        constraint = lp_mint.mint_authority == COption::Some(state.amm_pool)
+            @ MineballError::InvalidLpMint
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = quote_mint,
+        token::authority = state,
+        seeds = [LIQUIDITY_QUOTE_VAULT_SEED],
+        bump
+    )]
+    pub liquidity_quote_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = lp_mint,
+        token::authority = state,
+        seeds = [LP_VAULT_SEED],
+        bump
+    )]
+    pub lp_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Token program of the quote and LP mints
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Open the vaults liquidity provision needs. LP tokens received by the protocol
+/// are held in `lp_vault`, which has no withdraw instruction, so liquidity is locked.
+pub fn initialize_lp_vault(ctx: Context<InitializeLpVault>) -> Result<()> {
+    require_keys_neq!(
+        ctx.accounts.state.amm_pool,
+        Pubkey::default(),
+        MineballError::InvalidLpMint
+    );
+    
+    let state = &mut ctx.accounts.state;
+    
+    state.lp_mint = ctx.accounts.lp_mint.key();
+    
+    msg!("LP vault opened for LP mint {}", state.lp_mint);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        has_one = quote_mint @ MineballError::InvalidMint,
+        has_one = lp_mint @ MineballError::InvalidLpMint,
+        has_one = amm_program @ MineballError::InvalidAmm,
+        has_one = amm_pool @ MineballError::InvalidAmm,
+        constraint = !state.is_paused(PAUSE_LIQUIDITY) @ MineballError::ProtocolPaused
+    )]
+    pub state: Box<Account<'info, MineballState>>,
+    
     #[account(
         mut,
         seeds = [LIQUIDITY_POOL_SEED],
         bump
     )]
-    pub liquidity_pool: Account<'info, TokenAccount>,
+    pub liquidity_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_QUOTE_VAULT_SEED],
+        bump
+    )]
+    pub liquidity_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [LP_VAULT_SEED],
+        bump
+    )]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     
-    /// The account that will add liquidity (usually authority or automated bot)
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: pinned to the AMM stored at `initialize_buyback_vault`
+    pub amm_program: UncheckedAccount<'info>,
+    
+    /// CHECK: pinned to the pool stored at `initialize_buyback_vault`
+    pub amm_pool: UncheckedAccount<'info>,
+    
+    /// AMM vault of the quote mint, read for the keeper slippage check
+    #[account(
+        mut,
+        constraint = amm_quote_vault.mint == quote_mint.key() @ MineballError::InvalidMint,
+        constraint = amm_quote_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// AMM vault of the mineball mint, read for the keeper slippage check
+    #[account(
+        mut,
+        constraint = amm_token_vault.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = amm_token_vault.owner == amm_pool.key() @ MineballError::InvalidAmm
+    )]
+    pub amm_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_REGISTRY_SEED],
+        bump = keeper_registry.bump
+    )]
+    pub keeper_registry: Box<Account<'info, KeeperRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, executor.key().as_ref()],
+        bump = keeper_info.bump
+    )]
+    pub keeper_info: Box<Account<'info, Keeper>>,
+    
+    /// Registered keeper submitting the trigger
     pub executor: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    /// Receives the keeper bounty
+    #[account(
+        mut,
+        constraint = executor_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = executor_token_account.owner == executor.key() @ MineballError::InvalidOwner
+    )]
+    pub executor_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub lp_token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn add_liquidity(ctx: Context<AddLiquidity>) -> Result<()> {
-    let state = &mut ctx.accounts.state;
+/// Pair the liquidity pool with quote and deposit it into the configured AMM.
+///
+/// Half of the pool is swapped for the quote mint, then both sides are deposited
+/// and the LP tokens are kept in `lp_vault`. Whatever does not fit the pool ratio
+/// stays in the vaults for the next run. A `min_quote_out` looser than the keeper
+/// registry allows slashes the keeper instead.
+pub fn add_liquidity<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddLiquidity<'info>>,
+    min_quote_out: u64,
+    min_lp_out: u64,
+    deadline: i64,
+) -> Result<()> {
     let clock = Clock::get()?;
     
+    require!(clock.unix_timestamp <= deadline, MineballError::DeadlineExceeded);
+    require!(
+        clock.unix_timestamp >= ctx.accounts.keeper_info.cooldown_until,
+        MineballError::KeeperInCooldown
+    );
+    
     // Check if liquidity pool has reached threshold
     require!(
-        state.liquidity_pool >= state.liquidity_threshold,
+        ctx.accounts.state.liquidity_ready(),
         MineballError::LiquidityPoolBelowThreshold
     );
     
-    let liquidity_amount = state.liquidity_pool;
+    let accounts = ctx.accounts;
+    run_add_liquidity(
+        LiquidityAccounts {
+            state: &mut accounts.state,
+            liquidity_pool: &mut accounts.liquidity_pool,
+            liquidity_quote_vault: &mut accounts.liquidity_quote_vault,
+            lp_vault: &mut accounts.lp_vault,
+            mint: &accounts.mint,
+            quote_mint: &accounts.quote_mint,
+            lp_mint: &accounts.lp_mint,
+            amm_program: &accounts.amm_program,
+            amm_pool: &accounts.amm_pool,
+            amm_quote_vault: &accounts.amm_quote_vault,
+            amm_token_vault: &accounts.amm_token_vault,
+            keeper_registry: &mut accounts.keeper_registry,
+            keeper_info: &mut accounts.keeper_info,
+            executor: accounts.executor.key(),
+            executor_token_account: &accounts.executor_token_account,
+            quote_token_program: &accounts.quote_token_program,
+            token_program: &accounts.token_program,
+            lp_token_program: &accounts.lp_token_program,
+        },
+        ctx.remaining_accounts,
+        min_quote_out,
+        min_lp_out,
+        clock.unix_timestamp,
+    )?;
+    
+    Ok(())
+}
+
+/// Accounts a liquidity add moves tokens through, borrowed from `AddLiquidity` or `Crank`
+pub struct LiquidityAccounts<'a, 'info> {
+    pub state: &'a mut Account<'info, MineballState>,
+    pub liquidity_pool: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub liquidity_quote_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub lp_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub quote_mint: &'a InterfaceAccount<'info, Mint>,
+    pub lp_mint: &'a InterfaceAccount<'info, Mint>,
+    pub amm_program: &'a AccountInfo<'info>,
+    pub amm_pool: &'a AccountInfo<'info>,
+    pub amm_quote_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub amm_token_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub keeper_registry: &'a mut Account<'info, KeeperRegistry>,
+    pub keeper_info: &'a mut Account<'info, Keeper>,
+    pub executor: Pubkey,
+    pub executor_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub quote_token_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub lp_token_program: &'a AccountInfo<'info>,
+}
+
+/// Pay the keeper bounty, then pair and deposit the rest of the liquidity pool.
+/// Returns false when the keeper was penalized instead.
+pub fn run_add_liquidity<'info>(
+    accounts: LiquidityAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    min_quote_out: u64,
+    min_lp_out: u64,
+    now: i64,
+) -> Result<bool> {
+    // The keeper bounty comes off the top, the rest is paired and deposited
+    let pool_amount = accounts.state.liquidity_pool;
+    let keeper_bounty = apply_bps(pool_amount, accounts.state.keeper_bounty_bps)?;
+    let liquidity_amount = pool_amount
+        .checked_sub(keeper_bounty)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    // A trigger looser than the registry allows is penalized instead of executed
+    let swap_amount = liquidity_amount / 2;
+    let required = required_min_out(
+        accounts.keeper_registry,
+        swap_amount,
+        accounts.amm_token_vault.amount,
+        false,
+    )?;
+    if min_quote_out < required {
+        penalize_keeper(
+            accounts.keeper_registry,
+            accounts.keeper_info,
+            min_quote_out,
+            required,
+            now,
+        )?;
+        return Ok(false);
+    }
+    
+    let state_seeds = &[STATE_SEED, &[accounts.state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    if keeper_bounty > 0 {
+        transfer_tokens(
+            accounts.token_program,
+            &accounts.liquidity_pool.to_account_info(),
+            accounts.mint,
+            &accounts.executor_token_account.to_account_info(),
+            &accounts.state.to_account_info(),
+            remaining_accounts,
+            keeper_bounty,
+            signer_seeds,
+        )?;
+        accounts.liquidity_pool.reload()?;
+    }
+    
+    let token_before = accounts.liquidity_pool.amount;
+    let quote_before = accounts.liquidity_quote_vault.amount;
+    let lp_before = accounts.lp_vault.amount;
+    
+    // Swap half the pool for the quote side of the pair
+    amm::swap(
+        accounts.amm_program,
+        SwapAccounts {
+            pool: accounts.amm_pool.clone(),
+            authority: accounts.state.to_account_info(),
+            source: accounts.liquidity_pool.to_account_info(),
+            destination: accounts.liquidity_quote_vault.to_account_info(),
+            vault_in: accounts.amm_token_vault.to_account_info(),
+            vault_out: accounts.amm_quote_vault.to_account_info(),
+            mint_in: accounts.mint.to_account_info(),
+            mint_out: accounts.quote_mint.to_account_info(),
+            token_program_in: accounts.token_program.clone(),
+            token_program_out: accounts.quote_token_program.clone(),
+        },
+        remaining_accounts,
+        swap_amount,
+        min_quote_out,
+        signer_seeds,
+    )?;
+    
+    accounts.liquidity_quote_vault.reload()?;
+    let quote_received = accounts.liquidity_quote_vault.amount
+        .checked_sub(quote_before)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(quote_received >= min_quote_out, MineballError::SlippageExceeded);
+    
+    // Deposit the other half with all the quote on hand
+    let token_amount = liquidity_amount
+        .checked_sub(swap_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    let quote_amount = accounts.liquidity_quote_vault.amount;
+    
+    amm::deposit(
+        accounts.amm_program,
+        DepositAccounts {
+            pool: accounts.amm_pool.clone(),
+            authority: accounts.state.to_account_info(),
+            source_x: accounts.liquidity_pool.to_account_info(),
+            source_y: accounts.liquidity_quote_vault.to_account_info(),
+            vault_x: accounts.amm_token_vault.to_account_info(),
+            vault_y: accounts.amm_quote_vault.to_account_info(),
+            lp_mint: accounts.lp_mint.to_account_info(),
+            lp_destination: accounts.lp_vault.to_account_info(),
+            mint_x: accounts.mint.to_account_info(),
+            mint_y: accounts.quote_mint.to_account_info(),
+            token_program_x: accounts.token_program.clone(),
+            token_program_y: accounts.quote_token_program.clone(),
+            lp_token_program: accounts.lp_token_program.clone(),
+        },
+        remaining_accounts,
+        token_amount,
+        quote_amount,
+        min_lp_out,
+        signer_seeds,
+    )?;
+    
+    // Measure what actually moved rather than trusting the AMM
+    accounts.liquidity_pool.reload()?;
+    accounts.liquidity_quote_vault.reload()?;
+    accounts.lp_vault.reload()?;
+    
+    let tokens_used = token_before
+        .checked_sub(accounts.liquidity_pool.amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    let quote_deposited = quote_amount
+        .checked_sub(accounts.liquidity_quote_vault.amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    let lp_minted = accounts.lp_vault.amount
+        .checked_sub(lp_before)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(lp_minted >= min_lp_out, MineballError::SlippageExceeded);
     
-    // In a real implementation, this would:
-    // 1. Take tokens from the liquidity pool
-    // 2. Add them to the DEX liquidity pool (e.g., Raydium)
-    // 3. Receive LP tokens
-    //
-    // For this MVP, we simulate the execution
-    // You would integrate with Raydium/Orca here
+    let state = accounts.state;
     
     // Update state
     state.total_liquidity_added = state.total_liquidity_added
-        .checked_add(liquidity_amount)
+        .checked_add(tokens_used)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    state.liquidity_pool = state.liquidity_pool
+        .checked_sub(tokens_used)
+        .and_then(|v| v.checked_sub(keeper_bounty))
         .ok_or(MineballError::ArithmeticOverflow)?;
+    state.last_liquidity_add = now;
     
-    state.liquidity_pool = 0;
-    state.last_liquidity_add = clock.unix_timestamp;
+    let keeper_info = accounts.keeper_info;
+    keeper_info.triggers_executed = keeper_info.triggers_executed.saturating_add(1);
     
     // Emit event
     emit!(LiquidityAdded {
-        cycle: state.total_mining_cycles,
-        amount: liquidity_amount,
-        timestamp: clock.unix_timestamp,
+        cycle: state.current_cycle(),
+        amount: tokens_used,
+        quote_amount: quote_deposited,
+        lp_minted,
+        keeper: accounts.executor,
+        keeper_bounty,
+        timestamp: now,
     });
     
-    msg!("Liquidity added: {} tokens", liquidity_amount);
+    msg!("Liquidity added: {} tokens", tokens_used);
+    msg!("LP minted: {}", lp_minted);
     
-    Ok(())
+    assert_vault_backed(accounts.liquidity_pool, state.liquidity_pool)?;
+    
+    Ok(true)
 }
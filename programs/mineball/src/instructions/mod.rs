@@ -3,9 +3,35 @@ pub mod transfer;
 pub mod rewards;
 pub mod buyback;
 pub mod liquidity;
+pub mod hook;
+pub mod fee;
+pub mod keeper;
+pub mod admin;
+pub mod exemption;
+pub mod pool;
+pub mod limits;
+pub mod cycle;
+pub mod crank;
+pub mod sync;
+pub mod vesting;
+pub mod compound;
+pub mod stake;
 
 pub use initialize::*;
 pub use transfer::*;
 pub use rewards::*;
 pub use buyback::*;
 pub use liquidity::*;
+pub use hook::*;
+pub use fee::*;
+pub use keeper::*;
+pub use admin::*;
+pub use exemption::*;
+pub use pool::*;
+pub use limits::*;
+pub use cycle::*;
+pub use crank::*;
+pub use sync::*;
+pub use vesting::*;
+pub use compound::*;
+pub use stake::*;
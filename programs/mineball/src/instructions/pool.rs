@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::{
+    MineballState, RegisteredPool, TaxLedger, MineballError, PoolRegistered, PoolUnregistered,
+    constants::*,
+};
+
+#[derive(Accounts)]
+pub struct RegisterPool<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = RegisteredPool::LEN,
+        seeds = [REGISTERED_POOL_SEED, pool_token_account.key().as_ref()],
+        bump
+    )]
+    pub registered_pool: Account<'info, RegisteredPool>,
+    
+    /// Charged in transfer-hook mode for trades of accounts without a ledger
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TaxLedger::LEN,
+        seeds = [TAX_LEDGER_SEED, pool_token_account.key().as_ref()],
+        bump
+    )]
+    pub pool_tax_ledger: Account<'info, TaxLedger>,
+    
+    /// The AMM pool's token account of the mint
+    #[account(
+        constraint = pool_token_account.mint == mint.key() @ MineballError::InvalidMint
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(address = state.mint @ MineballError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Register an AMM pool token account. Transfers out of it pay `buy_tax_rate`
+/// and transfers into it pay `sell_tax_rate`.
+pub fn register_pool(ctx: Context<RegisterPool>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool_token_account = ctx.accounts.pool_token_account.key();
+    
+    // Anyone may have opened the pool's ledger already
+    ctx.accounts.pool_tax_ledger.initialize_if_needed(pool_token_account, ctx.bumps.pool_tax_ledger);
+    
+    let registered_pool = &mut ctx.accounts.registered_pool;
+    
+    registered_pool.token_account = pool_token_account;
+    registered_pool.registered_at = clock.unix_timestamp;
+    registered_pool.bump = ctx.bumps.registered_pool;
+    
+    emit!(PoolRegistered {
+        token_account: registered_pool.token_account,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Pool registered: {}", registered_pool.token_account);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnregisterPool<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ MineballError::Unauthorized,
+        constraint = state.authority != Pubkey::default() @ MineballError::AuthorityRenounced
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [REGISTERED_POOL_SEED, registered_pool.token_account.as_ref()],
+        bump = registered_pool.bump
+    )]
+    pub registered_pool: Account<'info, RegisteredPool>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Unregister a pool, taxing its transfers at `transfer_tax_rate` again
+pub fn unregister_pool(ctx: Context<UnregisterPool>) -> Result<()> {
+    emit!(PoolUnregistered {
+        token_account: ctx.accounts.registered_pool.token_account,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Pool unregistered: {}", ctx.accounts.registered_pool.token_account);
+    
+    Ok(())
+}
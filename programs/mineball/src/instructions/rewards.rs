@@ -1,16 +1,30 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::{
-    MineballState, HolderInfo, MineballError, RewardsDistributed, RewardsClaimed,
+    MineballState, HolderInfo, RewardEpoch, VestingSchedule, MineballError, RewardsDistributed,
+    RewardEpochClosed, RewardsClaimed, RewardsCompounded, RewardsVested,
     constants::*,
+    utils::{apply_bps, assert_vault_backed, epoch_leaf, transfer_tokens, verify_merkle_proof},
 };
 
+/// Off-chain snapshot committed instead of the on-chain accumulator
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct EpochCommitment {
+    /// Root of the (index, holder, amount) Merkle tree
+    pub merkle_root: [u8; 32],
+    
+    /// Number of leaves, which sizes the claimed bitmap
+    pub recipients: u32,
+}
+
 #[derive(Accounts)]
+#[instruction(commitment: Option<EpochCommitment>)]
 pub struct DistributeRewards<'info> {
     #[account(
         mut,
         seeds = [STATE_SEED],
-        bump = state.bump
+        bump = state.bump,
+        constraint = !state.is_paused(PAUSE_DISTRIBUTION) @ MineballError::ProtocolPaused
     )]
     pub state: Account<'info, MineballState>,
     
@@ -19,46 +33,163 @@ pub struct DistributeRewards<'info> {
         seeds = [REWARD_POOL_SEED],
         bump
     )]
-    pub reward_pool: Account<'info, TokenAccount>,
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(address = state.mint @ MineballError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// Caller triggering the distribution
+    pub caller: Signer<'info>,
+    
+    /// Receives the keeper bounty
+    #[account(
+        mut,
+        constraint = caller_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = caller_token_account.owner == caller.key() @ MineballError::InvalidOwner
+    )]
+    pub caller_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
     
-    pub mint: Account<'info, Mint>,
+    /// Only passed when committing a Merkle snapshot
+    #[account(
+        init,
+        payer = authority,
+        space = RewardEpoch::space(commitment.map_or(0, |c| c.recipients)),
+        seeds = [REWARD_EPOCH_SEED, state.next_epoch().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_epoch: Option<Account<'info, RewardEpoch>>,
+    
+    /// Snapshots are computed off-chain, so only the authority may commit one
+    #[account(
+        mut,
+        address = state.authority @ MineballError::Unauthorized
+    )]
+    pub authority: Option<Signer<'info>>,
+    
+    pub system_program: Option<Program<'info, System>>,
 }
 
-pub fn distribute_rewards(ctx: Context<DistributeRewards>) -> Result<()> {
-    let state = &mut ctx.accounts.state;
+pub fn distribute_rewards<'info>(
+    ctx: Context<'_, '_, '_, 'info, DistributeRewards<'info>>,
+    commitment: Option<EpochCommitment>,
+) -> Result<()> {
     let clock = Clock::get()?;
     
     // Check if reward pool has reached threshold
     require!(
-        state.mining_reward_pool >= state.reward_distribution_threshold,
+        ctx.accounts.state.rewards_ready(),
         MineballError::RewardPoolBelowThreshold
     );
     
-    let amount_to_distribute = state.mining_reward_pool;
+    // Pay the caller's bounty out of the pool before it is distributed
+    let (keeper_bounty, amount_to_distribute) = pay_reward_bounty(
+        RewardBountyAccounts {
+            state: &ctx.accounts.state,
+            reward_pool: &ctx.accounts.reward_pool,
+            mint: &ctx.accounts.mint,
+            caller_token_account: &ctx.accounts.caller_token_account,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.remaining_accounts,
+    )?;
     
-    // Update state
-    state.total_rewards_distributed = state.total_rewards_distributed
-        .checked_add(amount_to_distribute)
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    let state = &mut ctx.accounts.state;
+    let cycle = state.current_cycle();
     
-    state.mining_reward_pool = 0;
-    state.last_reward_distribution = clock.unix_timestamp;
-    state.total_mining_cycles = state.total_mining_cycles
-        .checked_add(1)
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    let recipients = match (commitment, ctx.accounts.reward_epoch.as_mut()) {
+        // Merkle mode: set the pool aside for holders to claim with proofs
+        (Some(commitment), Some(reward_epoch)) => {
+            require!(
+                commitment.recipients > 0 && commitment.recipients <= MAX_EPOCH_RECIPIENTS,
+                MineballError::InvalidRecipientCount
+            );
+    
+            let epoch = state.next_epoch();
+            reward_epoch.epoch = epoch;
+            reward_epoch.cycle = cycle;
+            reward_epoch.merkle_root = commitment.merkle_root;
+            reward_epoch.total_amount = amount_to_distribute;
+            reward_epoch.claimed_amount = 0;
+            reward_epoch.recipients = commitment.recipients;
+            reward_epoch.claimed_count = 0;
+            reward_epoch.created_at = clock.unix_timestamp;
+            reward_epoch.bump = ctx.bumps.reward_epoch;
+            reward_epoch.claimed_bitmap = vec![0; RewardEpoch::bitmap_len(commitment.recipients)];
+            state.epoch_count = epoch;
+    
+            commitment.recipients as u64
+        }
+        // Accumulator mode: spread the pool over every registered unit of holder balance.
+        // The tokens stay in the reward pool until holders claim them.
+        (None, None) => {
+            state.accrue_rewards(amount_to_distribute)?;
+    
+            state.reward_holders
+        }
+        _ => return err!(MineballError::InvalidRewardEpoch),
+    };
+    
+    // Update state
+    state.record_distribution(amount_to_distribute, clock.unix_timestamp)?;
     
     // Emit event
     emit!(RewardsDistributed {
-        cycle: state.total_mining_cycles,
+        cycle,
         amount: amount_to_distribute,
-        recipients: 0, // Will be updated when individual claims happen
+        recipients,
+        keeper: ctx.accounts.caller.key(),
+        keeper_bounty,
         timestamp: clock.unix_timestamp,
     });
     
     msg!("Rewards distributed: {} tokens", amount_to_distribute);
-    msg!("Mining cycle: {}", state.total_mining_cycles);
+    msg!("Mining cycle: {}", cycle);
     
-    Ok(())
+    assert_vault_backed(&mut ctx.accounts.reward_pool, state.reward_vault_liability()?)
+}
+
+/// Accounts the keeper bounty of a distribution moves through,
+/// borrowed from `DistributeRewards` or `Crank`
+pub struct RewardBountyAccounts<'a, 'info> {
+    pub state: &'a Account<'info, MineballState>,
+    pub reward_pool: &'a InterfaceAccount<'info, TokenAccount>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub caller_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+/// Pay the caller's bounty out of the reward pool.
+/// Returns the bounty and what is left to distribute.
+pub fn pay_reward_bounty<'info>(
+    accounts: RewardBountyAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<(u64, u64)> {
+    let state = accounts.state;
+    let keeper_bounty = apply_bps(state.mining_reward_pool, state.keeper_bounty_bps)?;
+    
+    if keeper_bounty > 0 {
+        let state_seeds = &[STATE_SEED, &[state.bump]];
+        let signer_seeds = &[&state_seeds[..]];
+    
+        transfer_tokens(
+            accounts.token_program,
+            &accounts.reward_pool.to_account_info(),
+            accounts.mint,
+            &accounts.caller_token_account.to_account_info(),
+            &state.to_account_info(),
+            remaining_accounts,
+            keeper_bounty,
+            signer_seeds,
+        )?;
+    }
+    
+    let amount_to_distribute = state.mining_reward_pool
+        .checked_sub(keeper_bounty)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    Ok((keeper_bounty, amount_to_distribute))
 }
 
 #[derive(Accounts)]
@@ -66,7 +197,8 @@ pub struct ClaimRewards<'info> {
     #[account(
         mut,
         seeds = [STATE_SEED],
-        bump = state.bump
+        bump = state.bump,
+        constraint = !state.is_paused(PAUSE_CLAIMS) @ MineballError::ProtocolPaused
     )]
     pub state: Account<'info, MineballState>,
     
@@ -79,37 +211,47 @@ pub struct ClaimRewards<'info> {
     )]
     pub holder_info: Account<'info, HolderInfo>,
     
+    /// Required once `vesting_duration` is set; the claim is locked here instead of paid out
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = VestingSchedule::LEN,
+        seeds = [VESTING_SEED, holder.key().as_ref()],
+        bump
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+    
     #[account(mut)]
     pub holder: Signer<'info>,
     
-    #[account(mut)]
-    pub holder_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = holder_token_account.owner == holder.key() @ MineballError::InvalidOwner
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
         seeds = [REWARD_POOL_SEED],
         bump
     )]
-    pub reward_pool: Account<'info, TokenAccount>,
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(address = state.mint @ MineballError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
     
-    pub mint: Account<'info, Mint>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>) -> Result<()> {
     let holder_info = &mut ctx.accounts.holder_info;
-    let state = &ctx.accounts.state;
+    let state = &mut ctx.accounts.state;
     let clock = Clock::get()?;
     
     // Initialize holder info if needed
-    if holder_info.holder == Pubkey::default() {
-        holder_info.holder = ctx.accounts.holder.key();
-        holder_info.total_rewards_earned = 0;
-        holder_info.last_claim = 0;
-        holder_info.pending_rewards = 0;
-        holder_info.bump = ctx.bumps.holder_info;
-    }
+    holder_info.initialize_if_needed(ctx.accounts.holder.key(), ctx.bumps.holder_info);
     
     // Check minimum claim interval
     require!(
@@ -117,41 +259,75 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
         MineballError::NoRewardsToClaim
     );
     
-    // Calculate holder's share based on their balance
-    let holder_balance = ctx.accounts.holder_token_account.amount;
-    let total_supply = ctx.accounts.mint.supply;
-    
-    // Proportional share of reward pool
-    let reward_amount = if total_supply > 0 {
-        (ctx.accounts.reward_pool.amount as u128)
-            .checked_mul(holder_balance as u128)
-            .and_then(|v| v.checked_div(total_supply as u128))
-            .and_then(|v| u64::try_from(v).ok())
-            .ok_or(MineballError::ArithmeticOverflow)?
-    } else {
-        0
-    };
+    if holder_info.compound {
+        // Restake instead of paying out; the tokens stay in the reward vault
+        let amount = holder_info.compound(state)?;
+        require!(amount > 0, MineballError::NoRewardsToClaim);
+        holder_info.last_claim = clock.unix_timestamp;
     
+        emit!(RewardsCompounded {
+            holder: ctx.accounts.holder.key(),
+            amount,
+            compounded_balance: holder_info.compounded_balance,
+            caller: ctx.accounts.holder.key(),
+            timestamp: clock.unix_timestamp,
+        });
+    
+        msg!("Rewards compounded: {} tokens", amount);
+    
+        return Ok(());
+    }
+    
+    // Settle rewards earned so far. Rewards compounded before the holder opted out
+    // leave the weight and are paid out with the claim.
+    let released = holder_info.release_compounded(state)?;
+    
+    let earned = holder_info.pending_rewards;
+    let reward_amount = earned
+        .checked_add(released)
+        .ok_or(MineballError::ArithmeticOverflow)?;
     require!(reward_amount > 0, MineballError::NoRewardsToClaim);
     
-    // Transfer rewards
-    let state_seeds = &[STATE_SEED, &[state.bump]];
-    let signer_seeds = &[&state_seeds[..]];
+    if state.vesting_duration > 0 {
+        // Lock the claim in the vesting schedule; the tokens stay in the reward vault
+        let vesting_schedule = ctx.accounts.vesting_schedule
+            .as_mut()
+            .ok_or(MineballError::VestingScheduleRequired)?;
+        vesting_schedule.initialize_if_needed(ctx.accounts.holder.key(), ctx.bumps.vesting_schedule);
+        vesting_schedule.lock(reward_amount, clock.unix_timestamp, state.vesting_duration)?;
     
-    let transfer_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.reward_pool.to_account_info(),
-            to: ctx.accounts.holder_token_account.to_account_info(),
-            authority: state.to_account_info(),
-        },
-        signer_seeds,
-    );
-    token::transfer(transfer_ctx, reward_amount)?;
+        state.record_vesting(reward_amount)?;
+    
+        emit!(RewardsVested {
+            holder: ctx.accounts.holder.key(),
+            amount: reward_amount,
+            locked_amount: vesting_schedule.locked_amount,
+            vesting_end: vesting_schedule.end,
+            timestamp: clock.unix_timestamp,
+        });
+    } else {
+        // Transfer rewards
+        let state_seeds = &[STATE_SEED, &[state.bump]];
+        let signer_seeds = &[&state_seeds[..]];
+    
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.reward_pool.to_account_info(),
+            &ctx.accounts.mint,
+            &ctx.accounts.holder_token_account.to_account_info(),
+            &state.to_account_info(),
+            ctx.remaining_accounts,
+            reward_amount,
+            signer_seeds,
+        )?;
+    
+        state.record_claim(reward_amount)?;
+    }
     
     // Update holder info
+    holder_info.pending_rewards = 0;
     holder_info.total_rewards_earned = holder_info.total_rewards_earned
-        .checked_add(reward_amount)
+        .checked_add(earned)
         .ok_or(MineballError::ArithmeticOverflow)?;
     holder_info.last_claim = clock.unix_timestamp;
     
@@ -164,5 +340,161 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
     
     msg!("Rewards claimed: {} tokens", reward_amount);
     
+    assert_vault_backed(&mut ctx.accounts.reward_pool, state.reward_vault_liability()?)
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct ClaimEpochReward<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_CLAIMS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_EPOCH_SEED, epoch.to_le_bytes().as_ref()],
+        bump = reward_epoch.bump
+    )]
+    pub reward_epoch: Account<'info, RewardEpoch>,
+    
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = holder_token_account.owner == holder.key() @ MineballError::InvalidOwner
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump
+    )]
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Claim a holder's allocation from a committed Merkle reward epoch
+pub fn claim_epoch_reward<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimEpochReward<'info>>,
+    _epoch: u64,
+    index: u32,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let reward_epoch = &mut ctx.accounts.reward_epoch;
+    let state = &mut ctx.accounts.state;
+    let clock = Clock::get()?;
+    
+    require!(amount > 0, MineballError::InvalidAmount);
+    require!(!reward_epoch.is_expired(clock.unix_timestamp), MineballError::EpochExpired);
+    require!(index < reward_epoch.recipients, MineballError::InvalidMerkleProof);
+    require!(!reward_epoch.is_claimed(index), MineballError::AlreadyClaimed);
+    
+    let leaf = epoch_leaf(index, &ctx.accounts.holder.key(), amount);
+    require!(
+        verify_merkle_proof(&proof, &reward_epoch.merkle_root, leaf),
+        MineballError::InvalidMerkleProof
+    );
+    
+    // A bad snapshot must not be able to drain more than the epoch was given
+    let claimed_amount = reward_epoch.claimed_amount
+        .checked_add(amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    require!(
+        claimed_amount <= reward_epoch.total_amount,
+        MineballError::InsufficientBalance
+    );
+    
+    reward_epoch.set_claimed(index);
+    reward_epoch.claimed_amount = claimed_amount;
+    reward_epoch.claimed_count = reward_epoch.claimed_count
+        .checked_add(1)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.record_claim(amount)?;
+    
+    let state_seeds = &[STATE_SEED, &[state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    transfer_tokens(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.reward_pool.to_account_info(),
+        &ctx.accounts.mint,
+        &ctx.accounts.holder_token_account.to_account_info(),
+        &state.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        signer_seeds,
+    )?;
+    
+    emit!(RewardsClaimed {
+        holder: ctx.accounts.holder.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Epoch {} reward claimed: {} tokens", reward_epoch.epoch, amount);
+    
+    assert_vault_backed(&mut ctx.accounts.reward_pool, state.reward_vault_liability()?)
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct CloseEpoch<'info> {
+    /// Paused with the claims, so nothing is recycled while holders can't claim it
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = !state.is_paused(PAUSE_CLAIMS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    /// Closed once its claim window is over, the rent goes to the caller
+    #[account(
+        mut,
+        close = caller,
+        seeds = [REWARD_EPOCH_SEED, epoch.to_le_bytes().as_ref()],
+        bump = reward_epoch.bump
+    )]
+    pub reward_epoch: Account<'info, RewardEpoch>,
+    
+    #[account(mut)]
+    pub caller: Signer<'info>,
+}
+
+/// Close an expired Merkle reward epoch and return what was never claimed
+/// to the mining reward pool
+pub fn close_epoch(ctx: Context<CloseEpoch>, _epoch: u64) -> Result<()> {
+    let reward_epoch = &ctx.accounts.reward_epoch;
+    let state = &mut ctx.accounts.state;
+    let clock = Clock::get()?;
+    
+    require!(reward_epoch.is_expired(clock.unix_timestamp), MineballError::EpochNotExpired);
+    
+    // The tokens never left the reward vault, only their booking moves
+    let unclaimed = reward_epoch.total_amount
+        .checked_sub(reward_epoch.claimed_amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    state.recycle_unclaimed(unclaimed)?;
+    
+    emit!(RewardEpochClosed {
+        epoch: reward_epoch.epoch,
+        cycle: reward_epoch.cycle,
+        unclaimed,
+        caller: ctx.accounts.caller.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Epoch {} closed: {} unclaimed tokens recycled", reward_epoch.epoch, unclaimed);
+    
     Ok(())
 }
@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, HolderInfo, StakePosition, MineballError, Staked, Unstaked, UnstakeWithdrawn,
+    constants::*,
+    utils::{assert_vault_backed, transfer_tokens},
+};
+
+#[derive(Accounts)]
+pub struct InitializeStakeVault<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = state,
+        seeds = [STAKE_VAULT_SEED],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Open the vault staked tokens are held in. Permissionless, it has nothing to configure.
+pub fn initialize_stake_vault(_ctx: Context<InitializeStakeVault>) -> Result<()> {
+    msg!("Stake vault opened");
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_STAKING) @ MineballError::ProtocolPaused
+    )]
+    pub state: Box<Account<'info, MineballState>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = HolderInfo::LEN,
+        seeds = [HOLDER_SEED, holder.key().as_ref()],
+        bump
+    )]
+    pub holder_info: Box<Account<'info, HolderInfo>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = StakePosition::LEN,
+        seeds = [STAKE_SEED, holder.key().as_ref()],
+        bump
+    )]
+    pub stake_position: Box<Account<'info, StakePosition>>,
+    
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = holder_token_account.owner == holder.key() @ MineballError::InvalidOwner
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED],
+        bump
+    )]
+    pub stake_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Move tokens into the stake vault, where they earn mining rewards
+pub fn stake<'info>(ctx: Context<'_, '_, '_, 'info, Stake<'info>>, amount: u64) -> Result<()> {
+    require!(amount > 0, MineballError::InvalidAmount);
+    
+    let clock = Clock::get()?;
+    let holder = ctx.accounts.holder.key();
+    
+    ctx.accounts.holder_info.initialize_if_needed(holder, ctx.bumps.holder_info);
+    ctx.accounts.stake_position.initialize_if_needed(holder, ctx.bumps.stake_position);
+    
+    let vault_before = ctx.accounts.stake_vault.amount;
+    
+    transfer_tokens(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.holder_token_account.to_account_info(),
+        &ctx.accounts.mint,
+        &ctx.accounts.stake_vault.to_account_info(),
+        &ctx.accounts.holder.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        &[],
+    )?;
+    
+    // In transfer-fee mode the mint withholds its fee, so credit what arrived
+    ctx.accounts.stake_vault.reload()?;
+    let received = ctx.accounts.stake_vault.amount
+        .checked_sub(vault_before)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    let state = &mut ctx.accounts.state;
+    let stake_position = &mut ctx.accounts.stake_position;
+    
+    stake_position.amount = stake_position.amount
+        .checked_add(received)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    stake_position.last_staked_at = clock.unix_timestamp;
+    state.total_staked = state.total_staked
+        .checked_add(received)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    // Settle rewards earned at the old stake, then re-weight at the new one
+    ctx.accounts.holder_info.update_balance(state, stake_position.amount)?;
+    
+    emit!(Staked {
+        holder,
+        amount: received,
+        staked_amount: stake_position.amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Staked {} tokens, {} in total", received, stake_position.amount);
+    
+    assert_vault_backed(&mut ctx.accounts.stake_vault, state.total_staked)
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_STAKING) @ MineballError::ProtocolPaused
+    )]
+    pub state: Box<Account<'info, MineballState>>,
+    
+    #[account(
+        mut,
+        seeds = [HOLDER_SEED, holder.key().as_ref()],
+        bump = holder_info.bump,
+        has_one = holder @ MineballError::Unauthorized
+    )]
+    pub holder_info: Box<Account<'info, HolderInfo>>,
+    
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, holder.key().as_ref()],
+        bump = stake_position.bump,
+        has_one = holder @ MineballError::Unauthorized
+    )]
+    pub stake_position: Box<Account<'info, StakePosition>>,
+    
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = holder_token_account.owner == holder.key() @ MineballError::InvalidOwner
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED],
+        bump
+    )]
+    pub stake_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Stop `amount` of the stake earning. Without an unstake cooldown the tokens are paid
+/// out at once; otherwise they wait it out and leave through `withdraw_unstaked`.
+pub fn unstake<'info>(ctx: Context<'_, '_, '_, 'info, Unstake<'info>>, amount: u64) -> Result<()> {
+    require!(amount > 0, MineballError::InvalidAmount);
+    
+    let clock = Clock::get()?;
+    let state = &mut ctx.accounts.state;
+    let stake_position = &mut ctx.accounts.stake_position;
+    
+    stake_position.amount = stake_position.amount
+        .checked_sub(amount)
+        .ok_or(MineballError::InsufficientStake)?;
+    
+    // Settle rewards earned at the old stake, then re-weight at the new one
+    ctx.accounts.holder_info.update_balance(state, stake_position.amount)?;
+    
+    let available_at = if state.unstake_cooldown > 0 {
+        stake_position.queue_unstake(amount, clock.unix_timestamp, state.unstake_cooldown)?;
+        stake_position.unstake_available_at
+    } else {
+        let state_seeds = &[STATE_SEED, &[state.bump]];
+        let signer_seeds = &[&state_seeds[..]];
+    
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.stake_vault.to_account_info(),
+            &ctx.accounts.mint,
+            &ctx.accounts.holder_token_account.to_account_info(),
+            &state.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            signer_seeds,
+        )?;
+    
+        state.total_staked = state.total_staked
+            .checked_sub(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        0
+    };
+    
+    emit!(Unstaked {
+        holder: ctx.accounts.holder.key(),
+        amount,
+        staked_amount: stake_position.amount,
+        available_at,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Unstaked {} tokens, {} still staked", amount, stake_position.amount);
+    
+    assert_vault_backed(&mut ctx.accounts.stake_vault, state.total_staked)
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnstaked<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_STAKING) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, holder.key().as_ref()],
+        bump = stake_position.bump,
+        has_one = holder @ MineballError::Unauthorized
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+    
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = holder_token_account.owner == holder.key() @ MineballError::InvalidOwner
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pay out the unstaked tokens once their cooldown has passed
+pub fn withdraw_unstaked<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawUnstaked<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+    let state = &mut ctx.accounts.state;
+    let stake_position = &mut ctx.accounts.stake_position;
+    
+    let amount = stake_position.pending_unstake;
+    require!(amount > 0, MineballError::NothingToWithdraw);
+    require!(
+        clock.unix_timestamp >= stake_position.unstake_available_at,
+        MineballError::UnstakeCooldownActive
+    );
+    
+    let state_seeds = &[STATE_SEED, &[state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    transfer_tokens(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.stake_vault.to_account_info(),
+        &ctx.accounts.mint,
+        &ctx.accounts.holder_token_account.to_account_info(),
+        &state.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        signer_seeds,
+    )?;
+    
+    stake_position.pending_unstake = 0;
+    state.total_staked = state.total_staked
+        .checked_sub(amount)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(UnstakeWithdrawn {
+        holder: ctx.accounts.holder.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Withdrew {} unstaked tokens", amount);
+    
+    assert_vault_backed(&mut ctx.accounts.stake_vault, state.total_staked)
+}
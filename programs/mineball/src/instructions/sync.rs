@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, MineballError, PoolsSynced, constants::*,
+    utils::{assert_pools_backed, transfer_tokens},
+};
+
+#[derive(Accounts)]
+pub struct SyncPools<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_DISTRIBUTION) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump
+    )]
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [BUYBACK_POOL_SEED],
+        bump
+    )]
+    pub buyback_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [LIQUIDITY_POOL_SEED],
+        bump
+    )]
+    pub liquidity_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Reconcile the pool counters with the vault balances.
+///
+/// Tokens a vault holds beyond its counter (donations, rounding dust) are swept into
+/// the mining reward pool; surpluses in the buyback and liquidity vaults are moved to
+/// the reward vault first. A vault holding less than its counter fails. Permissionless.
+pub fn sync_pools<'info>(ctx: Context<'_, '_, '_, 'info, SyncPools<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+    let state = &ctx.accounts.state;
+    
+    let reward_liability = state.reward_vault_liability()?;
+    let reward_surplus = ctx.accounts.reward_pool.amount
+        .checked_sub(reward_liability)
+        .ok_or(MineballError::PoolBalanceMismatch)?;
+    let buyback_surplus = ctx.accounts.buyback_pool.amount
+        .checked_sub(state.buyback_vault_liability()?)
+        .ok_or(MineballError::PoolBalanceMismatch)?;
+    let liquidity_surplus = ctx.accounts.liquidity_pool.amount
+        .checked_sub(state.liquidity_pool)
+        .ok_or(MineballError::PoolBalanceMismatch)?;
+    
+    let state_seeds = &[STATE_SEED, &[state.bump]];
+    let signer_seeds = &[&state_seeds[..]];
+    
+    let vault_surpluses = [
+        (ctx.accounts.buyback_pool.to_account_info(), buyback_surplus),
+        (ctx.accounts.liquidity_pool.to_account_info(), liquidity_surplus),
+    ];
+    
+    for (vault, surplus) in &vault_surpluses {
+        if *surplus > 0 {
+            transfer_tokens(
+                &ctx.accounts.token_program.to_account_info(),
+                vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.reward_pool.to_account_info(),
+                &state.to_account_info(),
+                ctx.remaining_accounts,
+                *surplus,
+                signer_seeds,
+            )?;
+        }
+    }
+    
+    // Credit what actually arrived, which is less than sent under a transfer fee
+    ctx.accounts.reward_pool.reload()?;
+    let swept = ctx.accounts.reward_pool.amount
+        .checked_sub(reward_liability)
+        .ok_or(MineballError::PoolBalanceMismatch)?;
+    
+    let state = &mut ctx.accounts.state;
+    state.mining_reward_pool = state.mining_reward_pool
+        .checked_add(swept)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    emit!(PoolsSynced {
+        reward_surplus,
+        buyback_surplus,
+        liquidity_surplus,
+        swept,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Pools synced, {} tokens swept into rewards", swept);
+    
+    assert_pools_backed(
+        state,
+        &mut ctx.accounts.reward_pool,
+        &mut ctx.accounts.buyback_pool,
+        &mut ctx.accounts.liquidity_pool,
+    )
+}
@@ -1,171 +1,314 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::{MineballState, MineballError, TransferWithTaxExecuted, constants::*};
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, HolderInfo, FeeExemption, MineballError, TaxMode,
+    TransferDirection, TransferWithTaxExecuted, ExemptTransferExecuted, constants::*,
+    instructions::limits::{check_transfer_limits, check_wallet_limit},
+    utils::{
+        advance_volume_epoch, assert_pools_backed, discounted_tax, is_registered_pool,
+        transfer_tokens,
+    },
+};
 
 #[derive(Accounts)]
 pub struct TransferWithTax<'info> {
     #[account(
         mut,
         seeds = [STATE_SEED],
-        bump = state.bump
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_TRANSFERS) @ MineballError::ProtocolPaused
     )]
     pub state: Account<'info, MineballState>,
     
     #[account(mut)]
     pub from: Signer<'info>,
     
-    #[account(mut)]
-    pub from_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = from_token_account.owner == from.key() @ MineballError::InvalidOwner
+    )]
+    pub from_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     
-    #[account(mut)]
-    pub to_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key() @ MineballError::InvalidMint
+    )]
+    pub to_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = from,
+        space = HolderInfo::LEN,
+        seeds = [HOLDER_SEED, from.key().as_ref()],
+        bump
+    )]
+    pub from_holder_info: Box<Account<'info, HolderInfo>>,
+    
+    /// Sender's fee exemption, if it has one
+    #[account(
+        seeds = [FEE_EXEMPTION_SEED, from.key().as_ref()],
+        bump = from_exemption.bump
+    )]
+    pub from_exemption: Option<Account<'info, FeeExemption>>,
+    
+    /// Recipient's fee exemption, if it has one
+    #[account(
+        seeds = [FEE_EXEMPTION_SEED, to_token_account.owner.as_ref()],
+        bump = to_exemption.bump
+    )]
+    pub to_exemption: Option<Account<'info, FeeExemption>>,
+    
+    /// CHECK: pool registration of the source token account, which may not exist.
+    /// Required so a buy can't be passed off as a plain transfer.
+    #[account(seeds = [REGISTERED_POOL_SEED, from_token_account.key().as_ref()], bump)]
+    pub from_pool: UncheckedAccount<'info>,
+    
+    /// CHECK: pool registration of the destination token account, which may not exist.
+    /// Required so a sell can't dodge the sell rate and limits.
+    #[account(seeds = [REGISTERED_POOL_SEED, to_token_account.key().as_ref()], bump)]
+    pub to_pool: UncheckedAccount<'info>,
     
     #[account(
         mut,
         seeds = [REWARD_POOL_SEED],
         bump
     )]
-    pub reward_pool: Account<'info, TokenAccount>,
+    pub reward_pool: Box<InterfaceAccount<'info, TokenAccount>>,
     
     #[account(
         mut,
         seeds = [BUYBACK_POOL_SEED],
         bump
     )]
-    pub buyback_pool: Account<'info, TokenAccount>,
+    pub buyback_pool: Box<InterfaceAccount<'info, TokenAccount>>,
     
     #[account(
         mut,
         seeds = [LIQUIDITY_POOL_SEED],
         bump
     )]
-    pub liquidity_pool: Account<'info, TokenAccount>,
+    pub liquidity_pool: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
     
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn transfer_with_tax(ctx: Context<TransferWithTax>, amount: u64) -> Result<()> {
+pub fn transfer_with_tax<'info>(
+    ctx: Context<'_, '_, '_, 'info, TransferWithTax<'info>>,
+    amount: u64,
+) -> Result<()> {
     require!(amount > 0, MineballError::InvalidAmount);
     
-    let state = &mut ctx.accounts.state;
+    // With the transfer hook active every transfer is already taxed
+    require!(
+        ctx.accounts.state.tax_mode == TaxMode::Instruction,
+        MineballError::InvalidTaxMode
+    );
+    
+    // Either side being exempt discounts the tax, the larger discount wins
+    let discount_bps = [&ctx.accounts.from_exemption, &ctx.accounts.to_exemption]
+        .iter()
+        .filter_map(|exemption| exemption.as_ref().map(|e| e.discount_bps))
+        .max()
+        .unwrap_or(0);
+    
+    let from_pool = is_registered_pool(&ctx.accounts.from_pool);
+    let to_pool = is_registered_pool(&ctx.accounts.to_pool);
+    let direction = TransferDirection::from_pools(from_pool, to_pool);
+    
     let clock = Clock::get()?;
     
+    // Registered pools and protocol vaults aren't held to the launch limits
+    let state_key = ctx.accounts.state.key();
+    let from_unlimited = from_pool;
+    let to_unlimited = to_pool || ctx.accounts.to_token_account.owner == state_key;
+    
+    check_transfer_limits(
+        &ctx.accounts.state,
+        ctx.accounts.from_holder_info.last_sell_at,
+        amount,
+        direction,
+        from_unlimited,
+        to_unlimited,
+        clock.unix_timestamp,
+    )?;
+    
+    let state = &mut ctx.accounts.state;
+    
     // Calculate tax
-    let tax_amount = (amount as u128)
-        .checked_mul(state.tax_rate as u128)
-        .and_then(|v| v.checked_div(BASIS_POINTS as u128))
-        .and_then(|v| u64::try_from(v).ok())
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    // Roll the volume window first so the transfer pays the tier now in force
+    advance_volume_epoch(state, clock.unix_timestamp);
+    let (volume_tier, _) = state.active_volume_tier(clock.unix_timestamp);
+    let tax_rate = state.effective_tax_rate(direction, clock.unix_timestamp)?;
+    let tax_amount = discounted_tax(amount, tax_rate, discount_bps)?;
     
     let net_amount = amount
         .checked_sub(tax_amount)
         .ok_or(MineballError::ArithmeticOverflow)?;
     
     // Calculate tax distribution
-    let mining_reward_amount = (tax_amount as u128)
-        .checked_mul(state.mining_reward_share as u128)
-        .and_then(|v| v.checked_div(BASIS_POINTS as u128))
-        .and_then(|v| u64::try_from(v).ok())
-        .ok_or(MineballError::ArithmeticOverflow)?;
-    
-    let buyback_amount = (tax_amount as u128)
-        .checked_mul(state.buyback_share as u128)
-        .and_then(|v| v.checked_div(BASIS_POINTS as u128))
-        .and_then(|v| u64::try_from(v).ok())
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    let split = state.split_tax(tax_amount, direction)?;
     
-    let liquidity_amount = (tax_amount as u128)
-        .checked_mul(state.liquidity_share as u128)
-        .and_then(|v| v.checked_div(BASIS_POINTS as u128))
-        .and_then(|v| u64::try_from(v).ok())
-        .ok_or(MineballError::ArithmeticOverflow)?;
-    
-    // Burn amount (remaining after other distributions)
-    let burn_amount = tax_amount
-        .checked_sub(mining_reward_amount)
-        .and_then(|v| v.checked_sub(buyback_amount))
-        .and_then(|v| v.checked_sub(liquidity_amount))
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let from_token_account = ctx.accounts.from_token_account.to_account_info();
+    let from = ctx.accounts.from.to_account_info();
     
     // Transfer net amount to recipient
-    let transfer_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.from_token_account.to_account_info(),
-            to: ctx.accounts.to_token_account.to_account_info(),
-            authority: ctx.accounts.from.to_account_info(),
-        },
-    );
-    token::transfer(transfer_ctx, net_amount)?;
+    transfer_tokens(
+        &token_program,
+        &from_token_account,
+        &ctx.accounts.mint,
+        &ctx.accounts.to_token_account.to_account_info(),
+        &from,
+        ctx.remaining_accounts,
+        net_amount,
+        &[],
+    )?;
     
     // Transfer mining rewards to pool
-    if mining_reward_amount > 0 {
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.from_token_account.to_account_info(),
-                to: ctx.accounts.reward_pool.to_account_info(),
-                authority: ctx.accounts.from.to_account_info(),
-            },
-        );
-        token::transfer(transfer_ctx, mining_reward_amount)?;
+    if split.mining_reward_amount > 0 {
+        transfer_tokens(
+            &token_program,
+            &from_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.reward_pool.to_account_info(),
+            &from,
+            ctx.remaining_accounts,
+            split.mining_reward_amount,
+            &[],
+        )?;
         state.mining_reward_pool = state.mining_reward_pool
-            .checked_add(mining_reward_amount)
+            .checked_add(split.mining_reward_amount)
             .ok_or(MineballError::ArithmeticOverflow)?;
     }
     
     // Transfer to buyback pool
-    if buyback_amount > 0 {
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.from_token_account.to_account_info(),
-                to: ctx.accounts.buyback_pool.to_account_info(),
-                authority: ctx.accounts.from.to_account_info(),
-            },
-        );
-        token::transfer(transfer_ctx, buyback_amount)?;
+    if split.buyback_amount > 0 {
+        transfer_tokens(
+            &token_program,
+            &from_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.buyback_pool.to_account_info(),
+            &from,
+            ctx.remaining_accounts,
+            split.buyback_amount,
+            &[],
+        )?;
         state.buyback_pool = state.buyback_pool
-            .checked_add(buyback_amount)
+            .checked_add(split.buyback_amount)
             .ok_or(MineballError::ArithmeticOverflow)?;
     }
     
     // Transfer to liquidity pool
-    if liquidity_amount > 0 {
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.from_token_account.to_account_info(),
-                to: ctx.accounts.liquidity_pool.to_account_info(),
-                authority: ctx.accounts.from.to_account_info(),
-            },
-        );
-        token::transfer(transfer_ctx, liquidity_amount)?;
+    if split.liquidity_amount > 0 {
+        transfer_tokens(
+            &token_program,
+            &from_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.liquidity_pool.to_account_info(),
+            &from,
+            ctx.remaining_accounts,
+            split.liquidity_amount,
+            &[],
+        )?;
         state.liquidity_pool = state.liquidity_pool
-            .checked_add(liquidity_amount)
+            .checked_add(split.liquidity_amount)
             .ok_or(MineballError::ArithmeticOverflow)?;
     }
     
-    // Track burn (tokens are left in from_account and effectively removed from circulation)
-    if burn_amount > 0 {
+    // Burn the burn share out of the sender's account
+    if split.burn_amount > 0 {
+        let burn_ctx = CpiContext::new(
+            token_program.clone(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: from_token_account.clone(),
+                authority: from.clone(),
+            },
+        );
+        token_interface::burn(burn_ctx, split.burn_amount)?;
+    
         state.total_burned = state.total_burned
-            .checked_add(burn_amount)
+            .checked_add(split.burn_amount)
             .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        // Keep the recorded supply in step with the mint
+        ctx.accounts.mint.reload()?;
+        state.total_supply = ctx.accounts.mint.supply;
     }
     
-    // Update total volume
-    state.total_volume = state.total_volume
-        .checked_add(amount)
-        .ok_or(MineballError::ArithmeticOverflow)?;
+    // Reward weights follow staked balances, so only the launch limits care about balances here
+    ctx.accounts.to_token_account.reload()?;
+    
+    check_wallet_limit(state, ctx.accounts.to_token_account.amount, to_unlimited)?;
+    
+    let from_holder_info = &mut ctx.accounts.from_holder_info;
+    from_holder_info.initialize_if_needed(ctx.accounts.from.key(), ctx.bumps.from_holder_info);
+    if direction == TransferDirection::Sell {
+        from_holder_info.last_sell_at = clock.unix_timestamp;
+    }
+    
+    // Update volume, leaving out fully exempt internal moves
+    if discount_bps < BASIS_POINTS {
+        state.record_volume(amount)?;
+    }
     
     // Emit event
-    emit!(TransferWithTaxExecuted {
-        from: ctx.accounts.from.key(),
-        to: ctx.accounts.to_token_account.owner,
-        amount,
-        tax_amount,
-        timestamp: clock.unix_timestamp,
-    });
+    if discount_bps > 0 {
+        emit!(ExemptTransferExecuted {
+            from: ctx.accounts.from.key(),
+            to: ctx.accounts.to_token_account.owner,
+            amount,
+            tax_amount,
+            tax_rate,
+            direction,
+            volume_tier,
+            discount_bps,
+            timestamp: clock.unix_timestamp,
+        });
+    } else {
+        emit!(TransferWithTaxExecuted {
+            from: ctx.accounts.from.key(),
+            to: ctx.accounts.to_token_account.owner,
+            amount,
+            tax_amount,
+            tax_rate,
+            direction,
+            volume_tier,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+    
+    assert_pools_backed(
+        state,
+        &mut ctx.accounts.reward_pool,
+        &mut ctx.accounts.buyback_pool,
+        &mut ctx.accounts.liquidity_pool,
+    )?;
     
     Ok(())
 }
+
+#[derive(Accounts)]
+pub struct GetEffectiveTaxRate<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump
+    )]
+    pub state: Account<'info, MineballState>,
+}
+
+/// Tax rate a transfer in `direction` pays right now, launch tax included
+pub fn get_effective_tax_rate(
+    ctx: Context<GetEffectiveTaxRate>,
+    direction: TransferDirection,
+) -> Result<u16> {
+    let clock = Clock::get()?;
+    
+    ctx.accounts.state.effective_tax_rate(direction, clock.unix_timestamp)
+}
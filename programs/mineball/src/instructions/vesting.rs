@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::{
+    MineballState, VestingSchedule, MineballError, VestedWithdrawn,
+    constants::*,
+    utils::{assert_vault_backed, transfer_tokens},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawVested<'info> {
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = mint @ MineballError::InvalidMint,
+        constraint = !state.is_paused(PAUSE_CLAIMS) @ MineballError::ProtocolPaused
+    )]
+    pub state: Account<'info, MineballState>,
+    
+    #[account(
+        mut,
+        seeds = [VESTING_SEED, holder.key().as_ref()],
+        bump = vesting_schedule.bump,
+        has_one = holder @ MineballError::Unauthorized
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key() @ MineballError::InvalidMint,
+        constraint = holder_token_account.owner == holder.key() @ MineballError::InvalidOwner
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump
+    )]
+    pub reward_pool: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Withdraw the unlocked part of a holder's vesting schedule.
+/// With `forfeit_unvested` the holder exits early: the still-locked tokens go back
+/// to the mining reward pool for the next distribution.
+pub fn withdraw_vested<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawVested<'info>>,
+    forfeit_unvested: bool,
+) -> Result<()> {
+    let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+    let state = &mut ctx.accounts.state;
+    let clock = Clock::get()?;
+    
+    let (amount, forfeited) = vesting_schedule.release(clock.unix_timestamp, forfeit_unvested)?;
+    require!(amount > 0 || forfeited > 0, MineballError::NothingToWithdraw);
+    
+    if amount > 0 {
+        let state_seeds = &[STATE_SEED, &[state.bump]];
+        let signer_seeds = &[&state_seeds[..]];
+    
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.reward_pool.to_account_info(),
+            &ctx.accounts.mint,
+            &ctx.accounts.holder_token_account.to_account_info(),
+            &state.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            signer_seeds,
+        )?;
+    }
+    
+    state.release_vesting(amount, forfeited)?;
+    
+    emit!(VestedWithdrawn {
+        holder: ctx.accounts.holder.key(),
+        amount,
+        forfeited,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Vested rewards withdrawn: {} tokens, {} forfeited", amount, forfeited);
+    
+    assert_vault_backed(&mut ctx.accounts.reward_pool, state.reward_vault_liability()?)
+}
@@ -7,6 +7,8 @@ pub mod instructions;
 pub mod errors;
 pub mod events;
 pub mod constants;
+pub mod utils;
+pub mod amm;
 
 pub use state::*;
 pub use instructions::*;
@@ -22,51 +24,360 @@ pub mod mineball {
     /// Sets up the global state and mining pools
     pub fn initialize(
         ctx: Context<Initialize>,
-        tax_rate: u16,
-        mining_reward_share: u16,
-        buyback_share: u16,
-        liquidity_share: u16,
-        burn_share: u16,
+        tax_rates: TaxRates,
+        buy_shares: TaxShares,
+        sell_shares: TaxShares,
+        transfer_shares: TaxShares,
+        keeper_bounty_bps: u16,
+        holder_terms: HolderTerms,
     ) -> Result<()> {
         instructions::initialize(
             ctx,
-            tax_rate,
-            mining_reward_share,
-            buyback_share,
-            liquidity_share,
-            burn_share,
+            tax_rates,
+            buy_shares,
+            sell_shares,
+            transfer_shares,
+            keeper_bounty_bps,
+            holder_terms,
         )
     }
 
     /// Process a transfer with tax
     /// Applies the tax split and routes funds to respective pools
-    pub fn transfer_with_tax(
-        ctx: Context<TransferWithTax>,
+    pub fn transfer_with_tax<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferWithTax<'info>>,
         amount: u64,
     ) -> Result<()> {
         instructions::transfer_with_tax(ctx, amount)
     }
 
     /// Distribute mining rewards to holders
-    /// Called when the reward pool reaches threshold
-    pub fn distribute_rewards(ctx: Context<DistributeRewards>) -> Result<()> {
-        instructions::distribute_rewards(ctx)
+    /// Called when the reward pool reaches threshold, optionally committing a Merkle snapshot
+    pub fn distribute_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeRewards<'info>>,
+        commitment: Option<EpochCommitment>,
+    ) -> Result<()> {
+        instructions::distribute_rewards(ctx, commitment)
     }
 
     /// Execute buyback from accumulated pool
-    /// Triggered when buyback pool reaches threshold
-    pub fn execute_buyback(ctx: Context<ExecuteBuyback>) -> Result<()> {
-        instructions::execute_buyback(ctx)
+    /// Triggered when the buyback vault reaches threshold, swaps through the configured AMM
+    pub fn execute_buyback<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteBuyback<'info>>,
+        min_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::execute_buyback(ctx, min_amount_out, deadline)
+    }
+
+    /// Deposit quote into the buyback vault
+    /// Permissionless, execute_buyback spends it once it reaches the threshold
+    pub fn deposit_buyback<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositBuyback<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_buyback(ctx, amount)
     }
 
     /// Add liquidity from accumulated pool
-    /// Triggered when liquidity pool reaches threshold
-    pub fn add_liquidity(ctx: Context<AddLiquidity>) -> Result<()> {
-        instructions::add_liquidity(ctx)
+    /// Triggered when liquidity pool reaches threshold, LP tokens are locked in the lp vault
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddLiquidity<'info>>,
+        min_quote_out: u64,
+        min_lp_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::add_liquidity(ctx, min_quote_out, min_lp_out, deadline)
     }
 
     /// Claim mining rewards as a holder
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>,
+    ) -> Result<()> {
         instructions::claim_rewards(ctx)
     }
+
+    /// Claim an allocation from a Merkle reward epoch
+    /// Each leaf index can be claimed once
+    pub fn claim_epoch_reward<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimEpochReward<'info>>,
+        epoch: u64,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_epoch_reward(ctx, epoch, index, amount, proof)
+    }
+
+    /// Close a Merkle reward epoch once its claim window is over
+    /// Permissionless, returns the unclaimed remainder to the mining reward pool
+    pub fn close_epoch(ctx: Context<CloseEpoch>, epoch: u64) -> Result<()> {
+        instructions::close_epoch(ctx, epoch)
+    }
+
+    /// Open the tax ledger a token account needs to send or buy tokens
+    /// while the transfer hook is active
+    pub fn initialize_tax_ledger(ctx: Context<InitializeTaxLedger>) -> Result<()> {
+        instructions::initialize_tax_ledger(ctx)
+    }
+
+    /// Record the tax owed on a Token-2022 transfer
+    /// Invoked by the transfer hook program during the transfer
+    pub fn record_hook_transfer(
+        ctx: Context<RecordHookTransfer>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::record_hook_transfer(ctx, amount)
+    }
+
+    /// Collect tax recorded by the transfer hook into the pools
+    /// Uses the state PDA's permanent delegate authority over the mint
+    pub fn collect_hook_tax<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectHookTax<'info>>,
+    ) -> Result<()> {
+        instructions::collect_hook_tax(ctx)
+    }
+
+    /// Harvest withheld transfer fees and split them across the pools
+    /// Holder token accounts to sweep are passed as remaining accounts
+    pub fn harvest_and_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestAndSplit<'info>>,
+    ) -> Result<()> {
+        instructions::harvest_and_split(ctx)
+    }
+
+    /// Open the quote vault buybacks are paid from
+    /// Pins the AMM program and pool every buyback trades on
+    pub fn initialize_buyback_vault(
+        ctx: Context<InitializeBuybackVault>,
+        burn_purchased: bool,
+        buyback_threshold: u64,
+    ) -> Result<()> {
+        instructions::initialize_buyback_vault(ctx, burn_purchased, buyback_threshold)
+    }
+
+    /// Open the quote and LP vaults used by add_liquidity
+    /// The LP vault has no withdraw path
+    pub fn initialize_lp_vault(ctx: Context<InitializeLpVault>) -> Result<()> {
+        instructions::initialize_lp_vault(ctx)
+    }
+
+    /// Create the keeper registry that gates the flywheel triggers
+    /// Sets the bond, slippage tolerance and penalties for keepers
+    pub fn initialize_keeper_registry(
+        ctx: Context<InitializeKeeperRegistry>,
+        config: KeeperConfig,
+    ) -> Result<()> {
+        instructions::initialize_keeper_registry(ctx, config)
+    }
+
+    /// Register as a keeper, posting a bond in lamports
+    /// Needs the authority's signature when the registry is permissioned
+    pub fn register_keeper(ctx: Context<RegisterKeeper>, bond: u64) -> Result<()> {
+        instructions::register_keeper(ctx, bond)
+    }
+
+    /// Remove a keeper and return its remaining bond
+    /// Callable by the keeper outside cooldown, or by the authority
+    pub fn deregister_keeper(ctx: Context<DeregisterKeeper>) -> Result<()> {
+        instructions::deregister_keeper(ctx)
+    }
+
+    /// Move the keepers' reference price towards the AMM spot price
+    /// Permissionless, at most once per PRICE_OBSERVATION_INTERVAL
+    pub fn observe_price(ctx: Context<ObservePrice>) -> Result<()> {
+        instructions::observe_price(ctx)
+    }
+
+    /// Withdraw slashed keeper bonds from the registry
+    /// Authority only
+    pub fn withdraw_slashed_bonds(ctx: Context<WithdrawSlashedBonds>) -> Result<()> {
+        instructions::withdraw_slashed_bonds(ctx)
+    }
+
+    /// Queue a change to thresholds, shares, tax rate or keeper bounty
+    /// Executable from `eta`, at least MIN_CONFIG_DELAY after queueing
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        update: ConfigUpdate,
+        eta: i64,
+    ) -> Result<()> {
+        instructions::queue_config_change(ctx, update, eta)
+    }
+
+    /// Apply the queued config change
+    /// Authority only, once the timelock has passed
+    pub fn execute_config_change(ctx: Context<ResolveConfigChange>) -> Result<()> {
+        instructions::execute_config_change(ctx)
+    }
+
+    /// Drop the queued config change
+    /// Authority only
+    pub fn cancel_config_change(ctx: Context<ResolveConfigChange>) -> Result<()> {
+        instructions::cancel_config_change(ctx)
+    }
+
+    /// Propose a new authority
+    /// Takes effect once the proposed key calls accept_authority
+    pub fn propose_authority(ctx: Context<AdminOnly>, new_authority: Pubkey) -> Result<()> {
+        instructions::propose_authority(ctx, new_authority)
+    }
+
+    /// Accept a proposed authority transfer
+    /// Signed by the pending authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority(ctx)
+    }
+
+    /// Renounce the authority permanently
+    /// Disables every admin instruction
+    pub fn renounce_authority(ctx: Context<AdminOnly>) -> Result<()> {
+        instructions::renounce_authority(ctx)
+    }
+
+    /// Set the guardian key that can pause instructions
+    /// Authority or the current guardian
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian(ctx, guardian)
+    }
+
+    /// Pause groups of instructions (PAUSE_* bitflags)
+    /// Guardian only
+    pub fn pause(ctx: Context<Pause>, flags: u8) -> Result<()> {
+        instructions::pause(ctx, flags)
+    }
+
+    /// Unpause groups of instructions
+    /// Authority only, or anyone once a pause is older than MAX_PAUSE_DURATION
+    pub fn unpause(ctx: Context<Unpause>, flags: u8) -> Result<()> {
+        instructions::unpause(ctx, flags)
+    }
+
+    /// Exempt an address from part or all of the transfer tax
+    /// Authority only, applies when the address sends or receives
+    pub fn add_fee_exemption(
+        ctx: Context<AddFeeExemption>,
+        address: Pubkey,
+        discount_bps: u16,
+    ) -> Result<()> {
+        instructions::add_fee_exemption(ctx, address, discount_bps)
+    }
+
+    /// Remove a fee exemption
+    /// Authority only
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>) -> Result<()> {
+        instructions::remove_fee_exemption(ctx)
+    }
+
+    /// Register an AMM pool token account for buy and sell tax rates
+    /// Authority only
+    pub fn register_pool(ctx: Context<RegisterPool>) -> Result<()> {
+        instructions::register_pool(ctx)
+    }
+
+    /// Unregister an AMM pool token account
+    /// Authority only
+    pub fn unregister_pool(ctx: Context<UnregisterPool>) -> Result<()> {
+        instructions::unregister_pool(ctx)
+    }
+
+    /// Tax rate a transfer in `direction` pays right now
+    /// Read-only, includes the decaying launch tax
+    pub fn get_effective_tax_rate(
+        ctx: Context<GetEffectiveTaxRate>,
+        direction: TransferDirection,
+    ) -> Result<u16> {
+        instructions::get_effective_tax_rate(ctx, direction)
+    }
+
+    /// Set the launch max-tx, max-wallet and sell-cooldown limits
+    /// Authority only, until the limits are lifted
+    pub fn set_trading_limits(
+        ctx: Context<AdminOnly>,
+        max_tx_amount: u64,
+        max_wallet_balance: u64,
+        min_seconds_between_sells: i64,
+    ) -> Result<()> {
+        instructions::set_trading_limits(
+            ctx,
+            max_tx_amount,
+            max_wallet_balance,
+            min_seconds_between_sells,
+        )
+    }
+
+    /// Lift the trading limits permanently
+    /// Authority only
+    pub fn lift_trading_limits(ctx: Context<AdminOnly>) -> Result<()> {
+        instructions::lift_trading_limits(ctx)
+    }
+
+    /// Complete the open mining cycle and open the next one
+    /// Permissionless, once the cycle has run its minimum duration
+    pub fn complete_cycle(ctx: Context<CompleteCycle>) -> Result<()> {
+        instructions::complete_cycle(ctx)
+    }
+
+    /// Close a completed mining cycle
+    /// Permissionless, refunds the rent to whoever paid it
+    pub fn close_cycle(ctx: Context<CloseCycle>, cycle: u64) -> Result<()> {
+        instructions::close_cycle(ctx, cycle)
+    }
+
+    /// Run every trigger that is ready and complete the mining cycle
+    /// Registered keepers only, skips the triggers below their threshold
+    pub fn crank<'info>(
+        ctx: Context<'_, '_, '_, 'info, Crank<'info>>,
+        params: CrankParams,
+    ) -> Result<()> {
+        instructions::crank(ctx, params)
+    }
+
+    /// Reconcile the pool counters with the vault balances
+    /// Permissionless, sweeps any surplus into the mining reward pool
+    pub fn sync_pools<'info>(ctx: Context<'_, '_, '_, 'info, SyncPools<'info>>) -> Result<()> {
+        instructions::sync_pools(ctx)
+    }
+
+    /// Withdraw unlocked vested rewards, optionally forfeiting the locked rest
+    /// back to the mining reward pool
+    pub fn withdraw_vested<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawVested<'info>>,
+        forfeit_unvested: bool,
+    ) -> Result<()> {
+        instructions::withdraw_vested(ctx, forfeit_unvested)
+    }
+
+    /// Opt in or out of restaking claimed rewards
+    pub fn set_compound_preference(ctx: Context<SetCompoundPreference>, enabled: bool) -> Result<()> {
+        instructions::set_compound_preference(ctx, enabled)
+    }
+
+    /// Settle and restake a holder's rewards on their behalf
+    /// Permissionless, only for holders who opted in
+    pub fn compound_for(ctx: Context<CompoundFor>) -> Result<()> {
+        instructions::compound_for(ctx)
+    }
+
+    /// Open the stake vault
+    pub fn initialize_stake_vault(ctx: Context<InitializeStakeVault>) -> Result<()> {
+        instructions::initialize_stake_vault(ctx)
+    }
+
+    /// Stake tokens so they earn mining rewards
+    pub fn stake<'info>(ctx: Context<'_, '_, '_, 'info, Stake<'info>>, amount: u64) -> Result<()> {
+        instructions::stake(ctx, amount)
+    }
+
+    /// Unstake tokens, paid out at once or after the unstake cooldown
+    pub fn unstake<'info>(ctx: Context<'_, '_, '_, 'info, Unstake<'info>>, amount: u64) -> Result<()> {
+        instructions::unstake(ctx, amount)
+    }
+
+    /// Withdraw unstaked tokens once their cooldown has passed
+    pub fn withdraw_unstaked<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawUnstaked<'info>>,
+    ) -> Result<()> {
+        instructions::withdraw_unstaked(ctx)
+    }
 }
@@ -1,4 +1,47 @@
 use anchor_lang::prelude::*;
+use crate::{MineballError, constants::*, utils::apply_bps};
+
+/// How the protocol collects its transfer tax
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaxMode {
+    /// Tax is only applied when holders route transfers through `transfer_with_tax`
+    Instruction,
+    
+    /// Tax is recorded on every Token-2022 transfer by the transfer hook
+    /// and collected afterwards through the mint's permanent delegate
+    TransferHook,
+    
+    /// Tax is withheld natively by the Token-2022 TransferFeeConfig extension
+    /// and routed to the pools by `harvest_and_split`
+    TransferFee,
+}
+
+/// Which tax rate a transfer pays, based on the registered AMM pools it touches
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// Neither side is a registered pool
+    Transfer,
+    
+    /// Tokens leave a registered pool
+    Buy,
+    
+    /// Tokens enter a registered pool
+    Sell,
+}
+
+impl TransferDirection {
+    /// Direction of a transfer given whether its source and destination are pools.
+    /// A pool-to-pool move counts as a buy.
+    pub fn from_pools(from_pool: bool, to_pool: bool) -> Self {
+        if from_pool {
+            TransferDirection::Buy
+        } else if to_pool {
+            TransferDirection::Sell
+        } else {
+            TransferDirection::Transfer
+        }
+    }
+}
 
 /// Global state for the mineball protocol
 #[account]
@@ -6,20 +49,65 @@ pub struct MineballState {
     /// Authority that can perform admin functions (renounced after setup)
     pub authority: Pubkey,
     
+    /// Authority proposed by `propose_authority`, waiting to accept
+    pub pending_authority: Pubkey,
+    
+    /// Key allowed to pause instructions in an emergency
+    pub guardian: Pubkey,
+    
+    /// Paused instruction groups (`PAUSE_*` bitflags)
+    pub paused: u8,
+    
+    /// When the last pause was set
+    pub paused_at: i64,
+    
     /// Mint address of the mineball token
     pub mint: Pubkey,
     
-    /// Total supply of tokens
+    /// Total supply of tokens, refreshed from the mint after every burn
     pub total_supply: u64,
     
-    /// Tax rate in basis points (e.g., 600 = 6%)
-    pub tax_rate: u16,
+    /// How the transfer tax is collected (derived from the mint at initialization)
+    pub tax_mode: TaxMode,
     
-    /// Tax distribution shares (in basis points, must sum to 10000)
-    pub mining_reward_share: u16,
-    pub buyback_share: u16,
-    pub liquidity_share: u16,
-    pub burn_share: u16,
+    /// Tax rates in basis points (e.g., 600 = 6%) for buys from, sells to and
+    /// transfers outside the registered AMM pools
+    pub buy_tax_rate: u16,
+    pub sell_tax_rate: u16,
+    pub transfer_tax_rate: u16,
+    
+    /// Anti-sniper tax that decays into the rates above after `created_at`
+    pub launch_tax: LaunchTaxSchedule,
+    
+    /// Anti-bot limits on `transfer_with_tax`, 0 disables each one
+    pub max_tx_amount: u64,
+    pub max_wallet_balance: u64,
+    pub min_seconds_between_sells: i64,
+    
+    /// Set by `lift_trading_limits`, after which the limits can never return
+    pub limits_lifted: bool,
+    
+    /// Rolling volume window: the current epoch and the last complete one
+    pub volume_epoch_start: i64,
+    pub epoch_volume: u64,
+    pub previous_epoch_volume: u64,
+    
+    /// Tiers that scale the tax rates by the last complete epoch's volume
+    pub volume_tiers: VolumeTierTable,
+    
+    /// Tax distribution shares of each direction
+    pub buy_shares: TaxShares,
+    pub sell_shares: TaxShares,
+    pub transfer_shares: TaxShares,
+    
+    /// Share of a triggered pool paid to the caller (in basis points)
+    pub keeper_bounty_bps: u16,
+    
+    /// Period over which claimed rewards unlock; zero pays claims out at once
+    pub vesting_duration: i64,
+    
+    /// Wait between unstaking and withdrawing; zero pays unstakes out at once
+    pub unstake_cooldown: i64,
     
     /// Pool balances
     pub mining_reward_pool: u64,
@@ -27,14 +115,53 @@ pub struct MineballState {
     pub liquidity_pool: u64,
     pub total_burned: u64,
     
-    /// Mining cycle tracking
+    /// Bought-back tokens and retired tax shares parked in the buyback pool vault,
+    /// kept apart from the tax share in `buyback_pool` waiting for the next buyback
+    pub bought_back: u64,
+    
+    /// Distributed rewards still in the reward vault, waiting to be claimed
+    pub unclaimed_rewards: u64,
+    
+    /// Claimed rewards still in the reward vault, locked in vesting schedules
+    pub vesting_rewards: u64,
+    
+    /// Claimed rewards restaked by holders, still in the reward vault
+    pub compounded_rewards: u64,
+    
+    /// Tokens in the stake vault, staked or waiting out the unstake cooldown
+    pub total_staked: u64,
+    
+    /// Completed mining cycles and lifetime totals
     pub total_mining_cycles: u64,
     pub total_volume: u64,
     pub total_rewards_distributed: u64,
     pub total_buyback_executed: u64,
     pub total_liquidity_added: u64,
     
-    /// Thresholds for automatic triggers
+    /// Rewards per unit of holder balance, scaled by ACC_REWARD_PRECISION
+    pub acc_reward_per_share: u128,
+    
+    /// Sum of the balances holders have registered for rewards
+    pub total_reward_weight: u64,
+    
+    /// Number of holders with a non-zero registered balance
+    pub reward_holders: u64,
+    
+    /// Merkle reward epochs committed so far, which numbers the next one
+    pub epoch_count: u64,
+    
+    /// Buyback venue, set by `initialize_buyback_vault`
+    pub quote_mint: Pubkey,
+    pub amm_program: Pubkey,
+    pub amm_pool: Pubkey,
+    
+    /// Burn bought-back tokens instead of parking them in the buyback pool
+    pub burn_buybacks: bool,
+    
+    /// LP mint of the AMM pool, held in the lp vault with no withdraw path
+    pub lp_mint: Pubkey,
+    
+    /// Thresholds for automatic triggers, the buyback one in quote units
     pub reward_distribution_threshold: u64,
     pub buyback_threshold: u64,
     pub liquidity_threshold: u64,
@@ -52,16 +179,575 @@ pub struct MineballState {
 impl MineballState {
     pub const LEN: usize = 8 + // discriminator
         32 + // authority
+        32 + // pending_authority
+        32 + // guardian
+        1 + // paused
+        8 + // paused_at
         32 + // mint
         8 + // total_supply
-        2 + // tax_rate
-        2 + 2 + 2 + 2 + // shares
+        1 + // tax_mode
+        2 + 2 + 2 + // tax rates
+        LaunchTaxSchedule::LEN + // launch_tax
+        8 + 8 + 8 + // trading limits
+        1 + // limits_lifted
+        8 + 8 + 8 + // volume window
+        VolumeTierTable::LEN + // volume_tiers
+        3 * TaxShares::LEN + // shares
+        2 + // keeper_bounty_bps
+        8 + // vesting_duration
+        8 + // unstake_cooldown
         8 + 8 + 8 + 8 + // pool balances
+        8 + // bought_back
+        8 + // unclaimed_rewards
+        8 + // vesting_rewards
+        8 + // compounded_rewards
+        8 + // total_staked
         8 + 8 + 8 + 8 + 8 + // cycle tracking
+        16 + // acc_reward_per_share
+        8 + // total_reward_weight
+        8 + // reward_holders
+        8 + // epoch_count
+        32 + 32 + 32 + // quote_mint, amm_program, amm_pool
+        1 + // burn_buybacks
+        32 + // lp_mint
         8 + 8 + 8 + // thresholds
         8 + 8 + 8 + 8 + // timestamps
         1 + // bump
         128; // padding
+    
+    /// Check the tax rates, shares and keeper bounty against their limits
+    pub fn validate_config(&self) -> Result<()> {
+        require!(
+            self.buy_tax_rate <= MAX_TAX_RATE
+                && self.sell_tax_rate <= MAX_TAX_RATE
+                && self.transfer_tax_rate <= MAX_TAX_RATE,
+            MineballError::InvalidTaxRate
+        );
+    
+        self.buy_shares.validate()?;
+        self.sell_shares.validate()?;
+        self.transfer_shares.validate()?;
+    
+        let max_rate = self.buy_tax_rate.max(self.sell_tax_rate).max(self.transfer_tax_rate);
+        self.volume_tiers.validate(max_rate)?;
+    
+        require!(
+            self.keeper_bounty_bps <= MAX_KEEPER_BOUNTY_BPS,
+            MineballError::InvalidKeeperBounty
+        );
+    
+        require!(
+            (0..=MAX_VESTING_DURATION).contains(&self.vesting_duration),
+            MineballError::InvalidVestingDuration
+        );
+    
+        require!(
+            (0..=MAX_UNSTAKE_COOLDOWN).contains(&self.unstake_cooldown),
+            MineballError::InvalidUnstakeCooldown
+        );
+    
+        Ok(())
+    }
+    
+    /// Whether any of the `PAUSE_*` bits in `flags` is set
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.paused & flags != 0
+    }
+    
+    /// Apply the fields set in `update` and validate the result
+    pub fn apply_config(&mut self, update: &ConfigUpdate) -> Result<()> {
+        // The transfer-fee extension charges its own rate, which the state must mirror
+        if self.tax_mode == TaxMode::TransferFee {
+            require!(
+                update.buy_tax_rate.unwrap_or(self.buy_tax_rate) == self.buy_tax_rate
+                    && update.sell_tax_rate.unwrap_or(self.sell_tax_rate) == self.sell_tax_rate
+                    && update.transfer_tax_rate.unwrap_or(self.transfer_tax_rate) == self.transfer_tax_rate
+                    && update.volume_tiers.map_or(0, |tiers| tiers.count) == 0,
+                MineballError::InvalidTaxMode
+            );
+        }
+    
+        if let Some(rate) = update.buy_tax_rate {
+            self.buy_tax_rate = rate;
+        }
+        if let Some(rate) = update.sell_tax_rate {
+            self.sell_tax_rate = rate;
+        }
+        if let Some(rate) = update.transfer_tax_rate {
+            self.transfer_tax_rate = rate;
+        }
+    
+        if let Some(shares) = update.buy_shares {
+            self.buy_shares = shares;
+        }
+        if let Some(shares) = update.sell_shares {
+            self.sell_shares = shares;
+        }
+        if let Some(shares) = update.transfer_shares {
+            self.transfer_shares = shares;
+        }
+        if let Some(bps) = update.keeper_bounty_bps {
+            self.keeper_bounty_bps = bps;
+        }
+        if let Some(duration) = update.vesting_duration {
+            self.vesting_duration = duration;
+        }
+        if let Some(cooldown) = update.unstake_cooldown {
+            self.unstake_cooldown = cooldown;
+        }
+    
+        if let Some(threshold) = update.reward_distribution_threshold {
+            self.reward_distribution_threshold = threshold;
+        }
+        if let Some(threshold) = update.buyback_threshold {
+            self.buyback_threshold = threshold;
+        }
+        if let Some(threshold) = update.liquidity_threshold {
+            self.liquidity_threshold = threshold;
+        }
+    
+        if let Some(tiers) = update.volume_tiers {
+            self.volume_tiers = tiers;
+        }
+    
+        self.validate_config()
+    }
+    
+    /// Tax rate that applies to a transfer in `direction`
+    pub fn tax_rate_for(&self, direction: TransferDirection) -> u16 {
+        match direction {
+            TransferDirection::Buy => self.buy_tax_rate,
+            TransferDirection::Sell => self.sell_tax_rate,
+            TransferDirection::Transfer => self.transfer_tax_rate,
+        }
+    }
+    
+    /// Tax rate of a transfer in `direction` at `now`, raised by the launch tax while it lasts
+    pub fn effective_tax_rate(&self, direction: TransferDirection, now: i64) -> Result<u16> {
+        let (_, rate_scale_bps) = self.active_volume_tier(now);
+        let base_rate = scale_rate(self.tax_rate_for(direction), rate_scale_bps);
+        let schedule = &self.launch_tax;
+    
+        let elapsed = now.saturating_sub(self.created_at).max(0);
+        if schedule.start_rate <= base_rate || elapsed >= schedule.duration {
+            return Ok(base_rate);
+        }
+    
+        // Stepwise schedules only drop at the end of each step
+        let elapsed = if schedule.step_seconds > 0 {
+            elapsed - elapsed % schedule.step_seconds
+        } else {
+            elapsed
+        };
+    
+        let excess = (schedule.start_rate - base_rate) as u64;
+        let remaining = (schedule.duration - elapsed) as u64;
+        let decayed = excess
+            .checked_mul(remaining)
+            .ok_or(MineballError::ArithmeticOverflow)?
+            / schedule.duration as u64;
+    
+        Ok(base_rate + decayed as u16)
+    }
+    
+    /// Volume of the last complete epoch as of `now`, counting skipped epochs as empty
+    pub fn last_epoch_volume(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.volume_epoch_start);
+    
+        if elapsed < VOLUME_EPOCH_DURATION {
+            self.previous_epoch_volume
+        } else if elapsed < 2 * VOLUME_EPOCH_DURATION {
+            self.epoch_volume
+        } else {
+            0
+        }
+    }
+    
+    /// Index of the volume tier in force at `now` and the scale it applies to the tax rates
+    pub fn active_volume_tier(&self, now: i64) -> (u8, u16) {
+        self.volume_tiers.tier_for(self.last_epoch_volume(now))
+    }
+    
+    /// Start a new volume epoch if the current one is over. Returns whether it rolled.
+    pub fn roll_volume_epoch(&mut self, now: i64) -> bool {
+        let elapsed = now.saturating_sub(self.volume_epoch_start);
+        if elapsed < VOLUME_EPOCH_DURATION {
+            return false;
+        }
+    
+        self.previous_epoch_volume = self.last_epoch_volume(now);
+        self.epoch_volume = 0;
+        self.volume_epoch_start = now - elapsed % VOLUME_EPOCH_DURATION;
+    
+        true
+    }
+    
+    /// Whether the reward pool has reached its distribution threshold
+    pub fn rewards_ready(&self) -> bool {
+        self.mining_reward_pool > 0
+            && self.mining_reward_pool >= self.reward_distribution_threshold
+    }
+    
+    /// Whether the buyback vault, holding `vault_amount` of the quote mint, has reached
+    /// its threshold, which is in quote units as well
+    pub fn buyback_ready(&self, vault_amount: u64) -> bool {
+        vault_amount > 0 && vault_amount >= self.buyback_threshold
+    }
+    
+    /// Whether the liquidity pool has reached its threshold
+    pub fn liquidity_ready(&self) -> bool {
+        self.liquidity_pool > 0 && self.liquidity_pool >= self.liquidity_threshold
+    }
+    
+    /// Spread `amount` over every registered unit of holder balance.
+    /// The tokens stay in the reward pool until holders claim them.
+    pub fn accrue_rewards(&mut self, amount: u64) -> Result<()> {
+        require!(self.total_reward_weight > 0, MineballError::NoRewardWeight);
+    
+        let reward_per_share = (amount as u128)
+            .checked_mul(ACC_REWARD_PRECISION)
+            .and_then(|v| v.checked_div(self.total_reward_weight as u128))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        self.acc_reward_per_share = self.acc_reward_per_share
+            .checked_add(reward_per_share)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Book a distribution of `amount` at `now`, emptying the reward pool.
+    /// The tokens stay in the vault as unclaimed rewards.
+    pub fn record_distribution(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.total_rewards_distributed = self.total_rewards_distributed
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.unclaimed_rewards = self.unclaimed_rewards
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        self.mining_reward_pool = 0;
+        self.last_reward_distribution = now;
+    
+        Ok(())
+    }
+    
+    /// Book a claim of `amount` out of the distributed rewards
+    pub fn record_claim(&mut self, amount: u64) -> Result<()> {
+        self.unclaimed_rewards = self.unclaimed_rewards
+            .checked_sub(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Return `amount` of unclaimed rewards from an expired epoch to the mining reward pool
+    pub fn recycle_unclaimed(&mut self, amount: u64) -> Result<()> {
+        self.record_claim(amount)?;
+        self.mining_reward_pool = self.mining_reward_pool
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Book a claim of `amount` locked into a vesting schedule instead of paid out
+    pub fn record_vesting(&mut self, amount: u64) -> Result<()> {
+        self.record_claim(amount)?;
+        self.vesting_rewards = self.vesting_rewards
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Release `withdrawn` vested tokens to a holder and recycle `forfeited` unvested
+    /// ones into the mining reward pool
+    pub fn release_vesting(&mut self, withdrawn: u64, forfeited: u64) -> Result<()> {
+        self.vesting_rewards = withdrawn
+            .checked_add(forfeited)
+            .and_then(|released| self.vesting_rewards.checked_sub(released))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.mining_reward_pool = self.mining_reward_pool
+            .checked_add(forfeited)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Book `amount` of unclaimed rewards restaked by a holder
+    pub fn record_compound(&mut self, amount: u64) -> Result<()> {
+        self.record_claim(amount)?;
+        self.compounded_rewards = self.compounded_rewards
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Return `amount` of restaked rewards to the unclaimed ones, to be claimed
+    pub fn release_compounded(&mut self, amount: u64) -> Result<()> {
+        self.compounded_rewards = self.compounded_rewards
+            .checked_sub(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.unclaimed_rewards = self.unclaimed_rewards
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Tokens the reward vault must hold: the undistributed pool plus unclaimed,
+    /// vesting and compounded rewards
+    pub fn reward_vault_liability(&self) -> Result<u64> {
+        let liability = self.mining_reward_pool
+            .checked_add(self.unclaimed_rewards)
+            .and_then(|v| v.checked_add(self.vesting_rewards))
+            .and_then(|v| v.checked_add(self.compounded_rewards))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(liability)
+    }
+    
+    /// Tokens the buyback pool vault must hold: the tax share waiting for the
+    /// next buyback plus parked purchases
+    pub fn buyback_vault_liability(&self) -> Result<u64> {
+        self.buyback_pool
+            .checked_add(self.bought_back)
+            .ok_or(error!(MineballError::ArithmeticOverflow))
+    }
+    
+    /// Mining cycle currently open, one past the completed ones
+    pub fn current_cycle(&self) -> u64 {
+        self.total_mining_cycles.saturating_add(1)
+    }
+    
+    /// Number of the next Merkle reward epoch, which seeds its account
+    pub fn next_epoch(&self) -> u64 {
+        self.epoch_count.saturating_add(1)
+    }
+    
+    /// Count `amount` towards the lifetime and current-epoch volume
+    pub fn record_volume(&mut self, amount: u64) -> Result<()> {
+        self.total_volume = self.total_volume
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.epoch_volume = self.epoch_volume
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Shares that split the tax of a transfer in `direction`
+    pub fn shares_for(&self, direction: TransferDirection) -> &TaxShares {
+        match direction {
+            TransferDirection::Buy => &self.buy_shares,
+            TransferDirection::Sell => &self.sell_shares,
+            TransferDirection::Transfer => &self.transfer_shares,
+        }
+    }
+    
+    /// Split the tax of a transfer in `direction` across the pools
+    pub fn split_tax(&self, tax_amount: u64, direction: TransferDirection) -> Result<TaxSplit> {
+        self.shares_for(direction).split(tax_amount)
+    }
+}
+
+/// Launch tax that starts at `start_rate` and decays linearly to the steady-state
+/// rates over `duration` seconds, or in steps of `step_seconds` when that is set
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LaunchTaxSchedule {
+    pub start_rate: u16,
+    pub duration: i64,
+    pub step_seconds: i64,
+}
+
+impl LaunchTaxSchedule {
+    pub const LEN: usize = 2 + 8 + 8;
+    
+    /// Check the schedule against its limits; a zero duration disables it
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.start_rate <= MAX_LAUNCH_TAX_RATE
+                && self.duration >= 0
+                && self.step_seconds >= 0
+                && self.step_seconds <= self.duration,
+            MineballError::InvalidLaunchTax
+        );
+    
+        Ok(())
+    }
+    
+    /// Whether the schedule raises any rate at all
+    pub fn is_enabled(&self) -> bool {
+        self.start_rate > 0 && self.duration > 0
+    }
+}
+
+/// Tax rates in force once an epoch's volume reaches `min_volume`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VolumeTier {
+    pub min_volume: u64,
+    
+    /// Scale applied to every base tax rate, 10000 leaves them unchanged
+    pub rate_scale_bps: u16,
+}
+
+/// Volume tiers in ascending `min_volume` order; the first `count` entries are used
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VolumeTierTable {
+    pub count: u8,
+    pub tiers: [VolumeTier; MAX_VOLUME_TIERS],
+}
+
+impl VolumeTierTable {
+    pub const LEN: usize = 1 + MAX_VOLUME_TIERS * (8 + 2);
+    
+    /// Check the table is ordered, starts at zero volume and never scales
+    /// `max_rate` above `MAX_TAX_RATE`. An empty table disables the tiers.
+    pub fn validate(&self, max_rate: u16) -> Result<()> {
+        require!(
+            self.count as usize <= MAX_VOLUME_TIERS,
+            MineballError::InvalidVolumeTiers
+        );
+    
+        let tiers = &self.tiers[..self.count as usize];
+        if let Some(first) = tiers.first() {
+            require!(first.min_volume == 0, MineballError::InvalidVolumeTiers);
+        }
+        require!(
+            tiers.windows(2).all(|pair| pair[0].min_volume < pair[1].min_volume),
+            MineballError::InvalidVolumeTiers
+        );
+    
+        for tier in tiers {
+            require!(
+                scale_rate(max_rate, tier.rate_scale_bps) <= MAX_TAX_RATE,
+                MineballError::InvalidTaxRate
+            );
+        }
+    
+        Ok(())
+    }
+    
+    /// Highest tier whose `min_volume` is reached and its rate scale.
+    /// Without tiers the rates are left unscaled.
+    pub fn tier_for(&self, volume: u64) -> (u8, u16) {
+        self.tiers[..self.count as usize]
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, tier)| volume >= tier.min_volume)
+            .map_or((0, BASIS_POINTS), |(index, tier)| (index as u8, tier.rate_scale_bps))
+    }
+}
+
+/// Scale a tax rate by `scale_bps`, rounding down and saturating at `u16::MAX`
+fn scale_rate(rate: u16, scale_bps: u16) -> u16 {
+    let scaled = rate as u32 * scale_bps as u32 / BASIS_POINTS as u32;
+    
+    scaled.min(u16::MAX as u32) as u16
+}
+
+/// Four-way split of a tax amount in basis points, summing to 10000
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaxShares {
+    pub mining_reward_share: u16,
+    pub buyback_share: u16,
+    pub liquidity_share: u16,
+    pub burn_share: u16,
+}
+
+impl TaxShares {
+    pub const LEN: usize = 2 + 2 + 2 + 2;
+    
+    /// Check that the shares sum to 100%
+    pub fn validate(&self) -> Result<()> {
+        let total_shares = self.mining_reward_share
+            .checked_add(self.buyback_share)
+            .and_then(|s| s.checked_add(self.liquidity_share))
+            .and_then(|s| s.checked_add(self.burn_share))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        require!(total_shares == BASIS_POINTS, MineballError::InvalidTaxShares);
+    
+        Ok(())
+    }
+    
+    /// Split a tax amount across the pools.
+    /// The burn portion takes the remainder so no dust is lost to rounding.
+    pub fn split(&self, tax_amount: u64) -> Result<TaxSplit> {
+        let mining_reward_amount = apply_bps(tax_amount, self.mining_reward_share)?;
+        let buyback_amount = apply_bps(tax_amount, self.buyback_share)?;
+        let liquidity_amount = apply_bps(tax_amount, self.liquidity_share)?;
+    
+        let burn_amount = tax_amount
+            .checked_sub(mining_reward_amount)
+            .and_then(|v| v.checked_sub(buyback_amount))
+            .and_then(|v| v.checked_sub(liquidity_amount))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(TaxSplit {
+            mining_reward_amount,
+            buyback_amount,
+            liquidity_amount,
+            burn_amount,
+        })
+    }
+}
+
+/// Per-destination breakdown of a tax amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaxSplit {
+    pub mining_reward_amount: u64,
+    pub buyback_amount: u64,
+    pub liquidity_amount: u64,
+    pub burn_amount: u64,
+}
+
+impl TaxSplit {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+    
+    /// Add another split destination by destination
+    pub fn checked_add(&self, other: &TaxSplit) -> Result<TaxSplit> {
+        let add = |a: u64, b: u64| a.checked_add(b).ok_or(MineballError::ArithmeticOverflow);
+    
+        Ok(TaxSplit {
+            mining_reward_amount: add(self.mining_reward_amount, other.mining_reward_amount)?,
+            buyback_amount: add(self.buyback_amount, other.buyback_amount)?,
+            liquidity_amount: add(self.liquidity_amount, other.liquidity_amount)?,
+            burn_amount: add(self.burn_amount, other.burn_amount)?,
+        })
+    }
+    
+    /// Scale the split of `total` down to `amount`.
+    /// As in `TaxShares::split`, the burn portion takes the rounding remainder.
+    pub fn prorate(&self, amount: u64, total: u64) -> Result<TaxSplit> {
+        if amount >= total {
+            return Ok(*self);
+        }
+    
+        let scale = |part: u64| -> Result<u64> {
+            u64::try_from((part as u128) * (amount as u128) / (total as u128))
+                .map_err(|_| MineballError::ArithmeticOverflow.into())
+        };
+        let mining_reward_amount = scale(self.mining_reward_amount)?;
+        let buyback_amount = scale(self.buyback_amount)?;
+        let liquidity_amount = scale(self.liquidity_amount)?;
+    
+        let burn_amount = amount
+            .checked_sub(mining_reward_amount)
+            .and_then(|v| v.checked_sub(buyback_amount))
+            .and_then(|v| v.checked_sub(liquidity_amount))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(TaxSplit {
+            mining_reward_amount,
+            buyback_amount,
+            liquidity_amount,
+            burn_amount,
+        })
+    }
 }
 
 /// Holder info for reward tracking
@@ -76,9 +762,24 @@ pub struct HolderInfo {
     /// Last claim timestamp
     pub last_claim: i64,
     
-    /// Pending rewards
+    /// Rewards settled but not yet claimed
     pub pending_rewards: u64,
     
+    /// Staked plus compounded balance currently counted towards the holder's reward weight
+    pub reward_balance: u64,
+    
+    /// Scaled rewards already accounted for at `reward_balance`
+    pub reward_debt: u128,
+    
+    /// Timestamp of the holder's last sell, for the sell cooldown
+    pub last_sell_at: i64,
+    
+    /// Whether claims are restaked instead of paid out
+    pub compound: bool,
+    
+    /// Restaked rewards held in the reward vault, counted in the reward weight
+    pub compounded_balance: u64,
+    
     /// Bump seed
     pub bump: u8,
 }
@@ -89,6 +790,901 @@ impl HolderInfo {
         8 + // total_rewards_earned
         8 + // last_claim
         8 + // pending_rewards
+        8 + // reward_balance
+        16 + // reward_debt
+        8 + // last_sell_at
+        1 + // compound
+        8 + // compounded_balance
         1 + // bump
-        64; // padding
+        47; // padding
+    
+    /// Fill in an account freshly created by `init_if_needed`
+    pub fn initialize_if_needed(&mut self, holder: Pubkey, bump: u8) {
+        if self.holder == Pubkey::default() {
+            self.holder = holder;
+            self.bump = bump;
+        }
+    }
+    
+    /// Move rewards accrued since the last update into `pending_rewards`
+    pub fn settle(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        let accrued = Self::accumulated(self.reward_balance, acc_reward_per_share)?
+            .checked_sub(self.reward_debt)
+            .and_then(|v| u64::try_from(v / ACC_REWARD_PRECISION).ok())
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        self.pending_rewards = self.pending_rewards
+            .checked_add(accrued)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        // Only the whole tokens paid out are added, so rounding dust carries over
+        self.reward_debt = (accrued as u128)
+            .checked_mul(ACC_REWARD_PRECISION)
+            .and_then(|v| v.checked_add(self.reward_debt))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Settle, then re-weight the holder at `staked_balance` plus their compounded rewards
+    pub fn update_balance(&mut self, state: &mut MineballState, staked_balance: u64) -> Result<()> {
+        let new_balance = staked_balance
+            .checked_add(self.compounded_balance)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        self.set_weight(state, new_balance)
+    }
+    
+    /// Settle, then count the holder at `new_balance` in the reward weight
+    fn set_weight(&mut self, state: &mut MineballState, new_balance: u64) -> Result<()> {
+        self.settle(state.acc_reward_per_share)?;
+    
+        state.total_reward_weight = state.total_reward_weight
+            .checked_sub(self.reward_balance)
+            .and_then(|v| v.checked_add(new_balance))
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        if self.reward_balance == 0 && new_balance > 0 {
+            state.reward_holders = state.reward_holders
+                .checked_add(1)
+                .ok_or(MineballError::ArithmeticOverflow)?;
+        } else if self.reward_balance > 0 && new_balance == 0 {
+            state.reward_holders = state.reward_holders
+                .checked_sub(1)
+                .ok_or(MineballError::ArithmeticOverflow)?;
+        }
+    
+        self.reward_balance = new_balance;
+        self.reward_debt = Self::accumulated(new_balance, state.acc_reward_per_share)?;
+    
+        Ok(())
+    }
+    
+    /// Restake the settled rewards into `compounded_balance`, adding them to the weight.
+    /// Returns the amount compounded.
+    pub fn compound(&mut self, state: &mut MineballState) -> Result<u64> {
+        self.settle(state.acc_reward_per_share)?;
+    
+        let amount = self.pending_rewards;
+        self.pending_rewards = 0;
+        self.compounded_balance = self.compounded_balance
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.total_rewards_earned = self.total_rewards_earned
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        state.record_compound(amount)?;
+    
+        let new_balance = self.reward_balance
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.set_weight(state, new_balance)?;
+    
+        Ok(amount)
+    }
+    
+    /// Settle, then take the compounded rewards out of the reward weight so they can
+    /// be claimed. Returns the amount released.
+    pub fn release_compounded(&mut self, state: &mut MineballState) -> Result<u64> {
+        self.settle(state.acc_reward_per_share)?;
+    
+        let amount = self.compounded_balance;
+        self.compounded_balance = 0;
+        state.release_compounded(amount)?;
+    
+        let new_balance = self.reward_balance
+            .checked_sub(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.set_weight(state, new_balance)?;
+    
+        Ok(amount)
+    }
+    
+    /// Scaled rewards owed to `balance` since the start of the accumulator
+    fn accumulated(balance: u64, acc_reward_per_share: u128) -> Result<u128> {
+        let value = (balance as u128)
+            .checked_mul(acc_reward_per_share)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(value)
+    }
+}
+
+/// Claimed mining rewards unlocking linearly for one holder
+#[account]
+pub struct VestingSchedule {
+    /// Holder's wallet address
+    pub holder: Pubkey,
+    
+    /// Tokens still locked, unlocking linearly until `end`
+    pub locked_amount: u64,
+    
+    /// Tokens unlocked and not yet withdrawn
+    pub unlocked_amount: u64,
+    
+    /// Timestamp `locked_amount` was last checkpointed at
+    pub start: i64,
+    
+    /// Timestamp the locked tokens are fully unlocked
+    pub end: i64,
+    
+    /// Lifetime tokens withdrawn
+    pub total_withdrawn: u64,
+    
+    /// Lifetime unvested tokens forfeited back to the reward pool
+    pub total_forfeited: u64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl VestingSchedule {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // holder
+        8 + // locked_amount
+        8 + // unlocked_amount
+        8 + 8 + // start, end
+        8 + // total_withdrawn
+        8 + // total_forfeited
+        1 + // bump
+        32; // padding
+    
+    /// Fill in an account freshly created by `init_if_needed`
+    pub fn initialize_if_needed(&mut self, holder: Pubkey, bump: u8) {
+        if self.holder == Pubkey::default() {
+            self.holder = holder;
+            self.bump = bump;
+        }
+    }
+    
+    /// Move the tokens unlocked since `start` from `locked_amount` to `unlocked_amount`.
+    /// The rest keeps unlocking at the same rate until `end`.
+    pub fn checkpoint(&mut self, now: i64) -> Result<()> {
+        let unlocked = if now >= self.end {
+            self.locked_amount
+        } else if now <= self.start {
+            0
+        } else {
+            (self.locked_amount as u128)
+                .checked_mul((now - self.start) as u128)
+                .and_then(|v| v.checked_div((self.end - self.start) as u128))
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or(MineballError::ArithmeticOverflow)?
+        };
+    
+        self.locked_amount = self.locked_amount
+            .checked_sub(unlocked)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.unlocked_amount = self.unlocked_amount
+            .checked_add(unlocked)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.start = self.start.max(now);
+    
+        Ok(())
+    }
+    
+    /// Lock `amount` more, restarting the schedule so everything still locked
+    /// unlocks over `duration` from now
+    pub fn lock(&mut self, amount: u64, now: i64, duration: i64) -> Result<()> {
+        self.checkpoint(now)?;
+    
+        self.locked_amount = self.locked_amount
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.start = now;
+        self.end = now
+            .checked_add(duration)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+    
+    /// Take out the unlocked tokens and, if `forfeit_unvested`, drop the locked ones.
+    /// Returns the (withdrawn, forfeited) amounts.
+    pub fn release(&mut self, now: i64, forfeit_unvested: bool) -> Result<(u64, u64)> {
+        self.checkpoint(now)?;
+    
+        let withdrawn = self.unlocked_amount;
+        let forfeited = if forfeit_unvested { self.locked_amount } else { 0 };
+    
+        self.unlocked_amount = 0;
+        self.locked_amount = self.locked_amount
+            .checked_sub(forfeited)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.total_withdrawn = self.total_withdrawn
+            .checked_add(withdrawn)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.total_forfeited = self.total_forfeited
+            .checked_add(forfeited)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok((withdrawn, forfeited))
+    }
+}
+
+/// Tokens a holder has staked in the stake vault; only these earn mining rewards
+#[account]
+pub struct StakePosition {
+    /// Holder's wallet address
+    pub holder: Pubkey,
+    
+    /// Tokens staked and counted in the reward weight
+    pub amount: u64,
+    
+    /// Tokens unstaked and waiting out the cooldown
+    pub pending_unstake: u64,
+    
+    /// Timestamp `pending_unstake` can be withdrawn from
+    pub unstake_available_at: i64,
+    
+    /// Last stake timestamp
+    pub last_staked_at: i64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl StakePosition {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // holder
+        8 + // amount
+        8 + // pending_unstake
+        8 + // unstake_available_at
+        8 + // last_staked_at
+        1 + // bump
+        32; // padding
+    
+    /// Fill in an account freshly created by `init_if_needed`
+    pub fn initialize_if_needed(&mut self, holder: Pubkey, bump: u8) {
+        if self.holder == Pubkey::default() {
+            self.holder = holder;
+            self.bump = bump;
+        }
+    }
+    
+    /// Queue `amount` for withdrawal after `cooldown`, restarting the wait for
+    /// everything already pending
+    pub fn queue_unstake(&mut self, amount: u64, now: i64, cooldown: i64) -> Result<()> {
+        self.pending_unstake = self.pending_unstake
+            .checked_add(amount)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.unstake_available_at = now
+            .checked_add(cooldown)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+    
+        Ok(())
+    }
+}
+
+/// Off-chain reward snapshot committed by a distribution
+#[account]
+pub struct RewardEpoch {
+    /// Sequence number of the epoch, which seeds its account
+    pub epoch: u64,
+    
+    /// Mining cycle the snapshot was taken in
+    pub cycle: u64,
+    
+    /// Root of the Merkle tree of (index, holder, amount) leaves
+    pub merkle_root: [u8; 32],
+    
+    /// Tokens set aside for the epoch
+    pub total_amount: u64,
+    
+    /// Tokens claimed so far
+    pub claimed_amount: u64,
+    
+    /// Number of leaves in the tree
+    pub recipients: u32,
+    
+    /// Number of leaves claimed so far
+    pub claimed_count: u32,
+    
+    /// Commit timestamp
+    pub created_at: i64,
+    
+    /// Bump seed
+    pub bump: u8,
+    
+    /// One bit per leaf index, set once claimed
+    pub claimed_bitmap: Vec<u8>,
+}
+
+impl RewardEpoch {
+    /// Account size for an epoch with `recipients` leaves
+    pub fn space(recipients: u32) -> usize {
+        8 + // discriminator
+        8 + // epoch
+        8 + // cycle
+        32 + // merkle_root
+        8 + // total_amount
+        8 + // claimed_amount
+        4 + // recipients
+        4 + // claimed_count
+        8 + // created_at
+        1 + // bump
+        4 + Self::bitmap_len(recipients) // claimed_bitmap
+    }
+    
+    pub fn bitmap_len(recipients: u32) -> usize {
+        (recipients as usize).div_ceil(8)
+    }
+    
+    /// Whether the claim window has closed at `now`
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.created_at.saturating_add(EPOCH_CLAIM_WINDOW)
+    }
+    
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed_bitmap[index as usize / 8] & (1 << (index % 8)) != 0
+    }
+    
+    pub fn set_claimed(&mut self, index: u32) {
+        self.claimed_bitmap[index as usize / 8] |= 1 << (index % 8);
+    }
+}
+
+/// Activity of one mining cycle, opened and closed by `complete_cycle`
+#[account]
+pub struct MiningCycle {
+    /// Cycle number, starting at 1
+    pub cycle: u64,
+    
+    /// When the cycle opened
+    pub started_at: i64,
+    
+    /// When the cycle completed, 0 while it is open
+    pub completed_at: i64,
+    
+    /// Lifetime totals of the state when the cycle opened
+    pub start_volume: u64,
+    pub start_rewards_distributed: u64,
+    pub start_buyback_executed: u64,
+    pub start_liquidity_added: u64,
+    
+    /// What the cycle added to each total, set when it completes
+    pub volume: u64,
+    pub rewards_distributed: u64,
+    pub buyback_executed: u64,
+    pub liquidity_added: u64,
+    
+    /// Paid the account's rent, refunded to it by `close_cycle`
+    pub payer: Pubkey,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl MiningCycle {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // cycle
+        8 + 8 + // started_at, completed_at
+        8 + 8 + 8 + 8 + // start totals
+        8 + 8 + 8 + 8 + // cycle deltas
+        32 + // payer
+        1 + // bump
+        32; // padding
+    
+    /// Open `cycle` at `now`, snapshotting the state's lifetime totals
+    pub fn open(&mut self, cycle: u64, state: &MineballState, payer: Pubkey, now: i64, bump: u8) {
+        self.cycle = cycle;
+        self.started_at = now;
+        self.completed_at = 0;
+        self.start_volume = state.total_volume;
+        self.start_rewards_distributed = state.total_rewards_distributed;
+        self.start_buyback_executed = state.total_buyback_executed;
+        self.start_liquidity_added = state.total_liquidity_added;
+        self.volume = 0;
+        self.rewards_distributed = 0;
+        self.buyback_executed = 0;
+        self.liquidity_added = 0;
+        self.payer = payer;
+        self.bump = bump;
+    }
+    
+    /// Open a freshly created first cycle. It runs from initialization,
+    /// when every total was zero, so its start totals stay zero.
+    pub fn open_first(&mut self, state: &MineballState, payer: Pubkey, bump: u8) {
+        if self.cycle == 0 {
+            self.cycle = state.current_cycle();
+            self.started_at = state.created_at;
+            self.payer = payer;
+            self.bump = bump;
+        }
+    }
+    
+    /// Close the cycle at `now`, recording how far each total moved since it opened
+    pub fn complete(&mut self, state: &MineballState, now: i64) -> Result<()> {
+        self.volume = state.total_volume
+            .checked_sub(self.start_volume)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.rewards_distributed = state.total_rewards_distributed
+            .checked_sub(self.start_rewards_distributed)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.buyback_executed = state.total_buyback_executed
+            .checked_sub(self.start_buyback_executed)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.liquidity_added = state.total_liquidity_added
+            .checked_sub(self.start_liquidity_added)
+            .ok_or(MineballError::ArithmeticOverflow)?;
+        self.completed_at = now;
+    
+        Ok(())
+    }
+}
+
+/// Transfer-hook tax owed by a single token account
+#[account]
+pub struct TaxLedger {
+    /// Token account the ledger belongs to
+    pub token_account: Pubkey,
+    
+    /// Tax recorded by the hook but not yet collected
+    pub tax_owed: u64,
+    
+    /// `tax_owed` split with the shares of each transfer's direction
+    pub owed_split: TaxSplit,
+    
+    /// Lifetime tax collected from this account
+    pub total_tax_paid: u64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl TaxLedger {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // token_account
+        8 + // tax_owed
+        TaxSplit::LEN + // owed_split
+        8 + // total_tax_paid
+        1 + // bump
+        32; // padding
+    
+    /// Fill in an account freshly created by `init_if_needed`
+    pub fn initialize_if_needed(&mut self, token_account: Pubkey, bump: u8) {
+        if self.token_account == Pubkey::default() {
+            self.token_account = token_account;
+            self.bump = bump;
+        }
+    }
+}
+
+/// Trigger-economics settings shared by every keeper
+#[account]
+pub struct KeeperRegistry {
+    /// Only the authority can add keepers when set; otherwise anyone posting the bond can
+    pub permissioned: bool,
+    
+    /// Minimum bond in lamports a keeper has to post
+    pub min_bond: u64,
+    
+    /// Loosest `min_amount_out` a trigger may send, in bps below the quote at `reference_price`
+    pub max_slippage_bps: u16,
+    
+    /// Share of a keeper's bond slashed for a trigger that fails the slippage check
+    pub slash_bps: u16,
+    
+    /// Seconds a keeper is locked out of triggers after failing the slippage check
+    pub cooldown_seconds: i64,
+    
+    /// Number of registered keepers
+    pub keeper_count: u64,
+    
+    /// Lamports slashed from keeper bonds over the registry's lifetime
+    pub total_slashed: u64,
+    
+    /// Quote per token scaled by PRICE_PRECISION that trigger minimums are checked
+    /// against. Seeded by the authority and moved towards spot by `observe_price`,
+    /// so a trigger can't move it within its own transaction.
+    pub reference_price: u128,
+    
+    /// Last `observe_price` update
+    pub price_observed_at: i64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl KeeperRegistry {
+    pub const LEN: usize = 8 + // discriminator
+        1 + // permissioned
+        8 + // min_bond
+        2 + // max_slippage_bps
+        2 + // slash_bps
+        8 + // cooldown_seconds
+        8 + // keeper_count
+        8 + // total_slashed
+        16 + // reference_price
+        8 + // price_observed_at
+        1 + // bump
+        40; // padding
+}
+
+/// A registered keeper allowed to fire the flywheel triggers
+#[account]
+pub struct Keeper {
+    /// Keeper wallet
+    pub keeper: Pubkey,
+    
+    /// Bond in lamports held by this account on top of rent
+    pub bond: u64,
+    
+    /// Registration timestamp
+    pub registered_at: i64,
+    
+    /// Triggers are rejected until this timestamp
+    pub cooldown_until: i64,
+    
+    /// Successful triggers submitted
+    pub triggers_executed: u64,
+    
+    /// Times the keeper was penalized
+    pub slash_count: u32,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl Keeper {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // keeper
+        8 + // bond
+        8 + // registered_at
+        8 + // cooldown_until
+        8 + // triggers_executed
+        4 + // slash_count
+        1 + // bump
+        32; // padding
+}
+
+/// Config fields to change, `None` leaves a field as it is
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct ConfigUpdate {
+    pub buy_tax_rate: Option<u16>,
+    pub sell_tax_rate: Option<u16>,
+    pub transfer_tax_rate: Option<u16>,
+    pub buy_shares: Option<TaxShares>,
+    pub sell_shares: Option<TaxShares>,
+    pub transfer_shares: Option<TaxShares>,
+    pub volume_tiers: Option<VolumeTierTable>,
+    pub keeper_bounty_bps: Option<u16>,
+    pub vesting_duration: Option<i64>,
+    pub unstake_cooldown: Option<i64>,
+    pub reward_distribution_threshold: Option<u64>,
+    pub buyback_threshold: Option<u64>,
+    pub liquidity_threshold: Option<u64>,
+}
+
+impl ConfigUpdate {
+    pub const LEN: usize = 4 * (1 + 2) + // tax rates, keeper_bounty_bps
+        3 * (1 + TaxShares::LEN) + // shares
+        1 + VolumeTierTable::LEN + // volume_tiers
+        1 + 8 + // vesting_duration
+        1 + 8 + // unstake_cooldown
+        3 * (1 + 8); // thresholds
+}
+
+/// Config change waiting out its timelock
+#[account]
+pub struct PendingConfigChange {
+    /// Fields the change will set
+    pub update: ConfigUpdate,
+    
+    /// Queue timestamp
+    pub queued_at: i64,
+    
+    /// Earliest timestamp the change can be executed
+    pub eta: i64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl PendingConfigChange {
+    pub const LEN: usize = 8 + // discriminator
+        ConfigUpdate::LEN + // update
+        8 + // queued_at
+        8 + // eta
+        1 + // bump
+        32; // padding
+}
+
+/// Tax discount for transfers to or from an address
+#[account]
+pub struct FeeExemption {
+    /// Wallet or program-owned account the exemption applies to
+    pub address: Pubkey,
+    
+    /// Share of the tax waived, 10000 is fully tax-free
+    pub discount_bps: u16,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl FeeExemption {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // address
+        2 + // discount_bps
+        8 + // created_at
+        1 + // bump
+        32; // padding
+}
+
+/// AMM pool token account whose transfers are taxed as buys and sells
+#[account]
+pub struct RegisteredPool {
+    /// Pool's token account of the mint
+    pub token_account: Pubkey,
+    
+    /// Registration timestamp
+    pub registered_at: i64,
+    
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl RegisteredPool {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // token_account
+        8 + // registered_at
+        1 + // bump
+        32; // padding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn vesting_schedule() -> VestingSchedule {
+        VestingSchedule {
+            holder: Pubkey::default(),
+            locked_amount: 0,
+            unlocked_amount: 0,
+            start: 0,
+            end: 0,
+            total_withdrawn: 0,
+            total_forfeited: 0,
+            bump: 0,
+        }
+    }
+    
+    /// Zeroed state with a 6% transfer tax and a 20% launch tax decaying over an hour
+    fn launch_state(step_seconds: i64) -> MineballState {
+        let mut state = MineballState::deserialize(&mut &[0u8; MineballState::LEN][..]).unwrap();
+        state.transfer_tax_rate = 600;
+        state.created_at = 1_000;
+        state.launch_tax = LaunchTaxSchedule {
+            start_rate: 2_000,
+            duration: 3_600,
+            step_seconds,
+        };
+    
+        state
+    }
+    
+    fn transfer_rate(state: &MineballState, elapsed: i64) -> u16 {
+        state
+            .effective_tax_rate(TransferDirection::Transfer, state.created_at + elapsed)
+            .unwrap()
+    }
+    
+    #[test]
+    fn launch_tax_decays_linearly() {
+        let state = launch_state(0);
+    
+        assert_eq!(transfer_rate(&state, -10), 2_000);
+        assert_eq!(transfer_rate(&state, 0), 2_000);
+        assert_eq!(transfer_rate(&state, 900), 1_650);
+        assert_eq!(transfer_rate(&state, 1_800), 1_300);
+        assert_eq!(transfer_rate(&state, 2_700), 950);
+        assert_eq!(transfer_rate(&state, 3_599), 600);
+        assert_eq!(transfer_rate(&state, 3_600), 600);
+        assert_eq!(transfer_rate(&state, 100_000), 600);
+    }
+    
+    #[test]
+    fn launch_tax_decays_in_steps() {
+        let state = launch_state(900);
+    
+        assert_eq!(transfer_rate(&state, 0), 2_000);
+        assert_eq!(transfer_rate(&state, 899), 2_000);
+        assert_eq!(transfer_rate(&state, 900), 1_650);
+        assert_eq!(transfer_rate(&state, 1_799), 1_650);
+        assert_eq!(transfer_rate(&state, 1_800), 1_300);
+        assert_eq!(transfer_rate(&state, 2_700), 950);
+        assert_eq!(transfer_rate(&state, 3_599), 950);
+        assert_eq!(transfer_rate(&state, 3_600), 600);
+        assert_eq!(transfer_rate(&state, 100_000), 600);
+    }
+    
+    #[test]
+    fn launch_tax_never_lowers_a_higher_base_rate() {
+        let mut state = launch_state(0);
+        state.sell_tax_rate = 900;
+        state.launch_tax.start_rate = 800;
+    
+        let now = state.created_at;
+        assert_eq!(state.effective_tax_rate(TransferDirection::Sell, now).unwrap(), 900);
+        assert_eq!(state.effective_tax_rate(TransferDirection::Transfer, now).unwrap(), 800);
+    }
+    
+    /// Full rates below 1000 of volume, 80% from there and half from 5000
+    fn volume_tiers() -> VolumeTierTable {
+        let mut table = VolumeTierTable {
+            count: 3,
+            ..Default::default()
+        };
+        table.tiers[0] = VolumeTier { min_volume: 0, rate_scale_bps: 10_000 };
+        table.tiers[1] = VolumeTier { min_volume: 1_000, rate_scale_bps: 8_000 };
+        table.tiers[2] = VolumeTier { min_volume: 5_000, rate_scale_bps: 5_000 };
+    
+        table
+    }
+    
+    fn error_code(result: Result<()>) -> u32 {
+        match result {
+            Err(Error::AnchorError(error)) => error.error_code_number,
+            other => panic!("expected an anchor error, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn volume_tier_for_picks_the_highest_tier_reached() {
+        let table = volume_tiers();
+    
+        assert_eq!(table.tier_for(0), (0, 10_000));
+        assert_eq!(table.tier_for(999), (0, 10_000));
+        assert_eq!(table.tier_for(1_000), (1, 8_000));
+        assert_eq!(table.tier_for(4_999), (1, 8_000));
+        assert_eq!(table.tier_for(5_000), (2, 5_000));
+        assert_eq!(table.tier_for(u64::MAX), (2, 5_000));
+    
+        // Without tiers the rates are left alone
+        assert_eq!(VolumeTierTable::default().tier_for(u64::MAX), (0, 10_000));
+    }
+    
+    #[test]
+    fn volume_tiers_validate() {
+        assert!(volume_tiers().validate(MAX_TAX_RATE).is_ok());
+        assert!(VolumeTierTable::default().validate(MAX_TAX_RATE).is_ok());
+    
+        let mut table = volume_tiers();
+        table.tiers[0].min_volume = 1;
+        assert_eq!(error_code(table.validate(900)), u32::from(MineballError::InvalidVolumeTiers));
+    
+        let mut table = volume_tiers();
+        table.tiers[2].min_volume = 1_000;
+        assert_eq!(error_code(table.validate(900)), u32::from(MineballError::InvalidVolumeTiers));
+    
+        let mut table = volume_tiers();
+        table.count = MAX_VOLUME_TIERS as u8 + 1;
+        assert_eq!(error_code(table.validate(900)), u32::from(MineballError::InvalidVolumeTiers));
+    
+        // A tier may raise the rates, but not past the cap
+        let mut table = volume_tiers();
+        table.tiers[1].rate_scale_bps = 11_000;
+        assert!(table.validate(900).is_ok());
+        assert_eq!(error_code(table.validate(1_000)), u32::from(MineballError::InvalidTaxRate));
+    }
+    
+    #[test]
+    fn volume_epoch_rolls_into_the_scaled_rate() {
+        let mut state = launch_state(0);
+        state.launch_tax = LaunchTaxSchedule::default();
+        state.volume_tiers = volume_tiers();
+        let rate = |state: &MineballState, now: i64| {
+            state.effective_tax_rate(TransferDirection::Transfer, now).unwrap()
+        };
+    
+        // Volume only counts once its epoch is complete
+        state.record_volume(1_500).unwrap();
+        assert!(!state.roll_volume_epoch(VOLUME_EPOCH_DURATION - 1));
+        assert_eq!(rate(&state, VOLUME_EPOCH_DURATION - 1), 600);
+    
+        let now = VOLUME_EPOCH_DURATION + 50;
+        assert!(state.roll_volume_epoch(now));
+        assert_eq!((state.previous_epoch_volume, state.epoch_volume), (1_500, 0));
+        assert_eq!(state.volume_epoch_start, VOLUME_EPOCH_DURATION);
+        assert_eq!(state.active_volume_tier(now), (1, 8_000));
+        assert_eq!(rate(&state, now), 480);
+    
+        state.record_volume(6_000).unwrap();
+        let now = 2 * VOLUME_EPOCH_DURATION;
+        assert!(state.roll_volume_epoch(now));
+        assert_eq!(state.active_volume_tier(now), (2, 5_000));
+        assert_eq!(rate(&state, now), 300);
+    }
+    
+    #[test]
+    fn volume_epoch_gap_resets_the_volume() {
+        let mut state = launch_state(0);
+        state.launch_tax = LaunchTaxSchedule::default();
+        state.volume_tiers = volume_tiers();
+        state.record_volume(6_000).unwrap();
+    
+        // A whole epoch went by without a transfer, so the last complete one was empty
+        let now = 2 * VOLUME_EPOCH_DURATION + 10;
+        assert_eq!(state.active_volume_tier(now), (0, 10_000));
+        assert!(state.roll_volume_epoch(now));
+        assert_eq!((state.previous_epoch_volume, state.epoch_volume), (0, 0));
+        assert_eq!(state.volume_epoch_start, 2 * VOLUME_EPOCH_DURATION);
+        assert_eq!(
+            state.effective_tax_rate(TransferDirection::Transfer, now).unwrap(),
+            600
+        );
+    }
+    
+    #[test]
+    fn vesting_unlocks_linearly() {
+        let mut schedule = vesting_schedule();
+        schedule.lock(1_000, 100, 100).unwrap();
+        assert_eq!((schedule.start, schedule.end), (100, 200));
+    
+        schedule.checkpoint(100).unwrap();
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (1_000, 0));
+    
+        schedule.checkpoint(125).unwrap();
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (750, 250));
+    
+        // The rest keeps unlocking at the same rate from the checkpoint
+        schedule.checkpoint(150).unwrap();
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (500, 500));
+    
+        schedule.checkpoint(250).unwrap();
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (0, 1_000));
+    }
+    
+    #[test]
+    fn vesting_lock_restarts_the_remaining_schedule() {
+        let mut schedule = vesting_schedule();
+        schedule.lock(1_000, 0, 100).unwrap();
+        schedule.lock(500, 50, 100).unwrap();
+    
+        // Half of the first lock had unlocked; the other half joins the new one
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (1_000, 500));
+        assert_eq!((schedule.start, schedule.end), (50, 150));
+    
+        schedule.checkpoint(100).unwrap();
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (500, 1_000));
+    }
+    
+    #[test]
+    fn vesting_release_forfeits_only_when_asked() {
+        let mut schedule = vesting_schedule();
+        schedule.lock(1_000, 0, 100).unwrap();
+    
+        assert_eq!(schedule.release(40, false).unwrap(), (400, 0));
+        assert_eq!(schedule.locked_amount, 600);
+    
+        assert_eq!(schedule.release(70, true).unwrap(), (300, 300));
+        assert_eq!((schedule.locked_amount, schedule.unlocked_amount), (0, 0));
+        assert_eq!((schedule.total_withdrawn, schedule.total_forfeited), (700, 300));
+    
+        assert_eq!(schedule.release(100, true).unwrap(), (0, 0));
+    }
 }
@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token_interface::{spl_token_2022::onchain::invoke_transfer_checked, Mint, TokenAccount};
+use crate::{FeeExemption, MineballState, MineballError, VolumeEpochRolled, constants::*};
+
+/// Apply a basis-point rate to an amount, rounding down
+pub fn apply_bps(amount: u64, bps: u16) -> Result<u64> {
+    let value = (amount as u128)
+        .checked_mul(bps as u128)
+        .and_then(|v| v.checked_div(BASIS_POINTS as u128))
+        .and_then(|v| u64::try_from(v).ok())
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    Ok(value)
+}
+
+/// Constant-product output for `amount_in` against the given reserves, before fees
+pub fn constant_product_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    let denominator = (reserve_in as u128)
+        .checked_add(amount_in as u128)
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    if denominator == 0 {
+        return Ok(0);
+    }
+    
+    let value = (amount_in as u128)
+        .checked_mul(reserve_out as u128)
+        .and_then(|v| v.checked_div(denominator))
+        .and_then(|v| u64::try_from(v).ok())
+        .ok_or(MineballError::ArithmeticOverflow)?;
+    
+    Ok(value)
+}
+
+/// Start a new volume epoch if the current one is over and announce the tier it brings
+pub fn advance_volume_epoch(state: &mut MineballState, now: i64) {
+    if state.roll_volume_epoch(now) {
+        let (volume_tier, _) = state.active_volume_tier(now);
+    
+        emit!(VolumeEpochRolled {
+            previous_epoch_volume: state.previous_epoch_volume,
+            volume_tier,
+            timestamp: now,
+        });
+    }
+}
+
+/// Tax owed on `amount` at `tax_rate` after a fee-exemption discount
+pub fn discounted_tax(amount: u64, tax_rate: u16, discount_bps: u16) -> Result<u64> {
+    let tax_amount = apply_bps(amount, tax_rate)?;
+    let discount = apply_bps(tax_amount, discount_bps)?;
+    
+    Ok(tax_amount - discount)
+}
+
+/// Discount of a `FeeExemption` passed as an unchecked account, 0 if it doesn't exist.
+/// The caller is responsible for checking the account's address.
+pub fn exemption_discount(account: &AccountInfo) -> Result<u16> {
+    if account.owner != &crate::ID || account.data_is_empty() {
+        return Ok(0);
+    }
+    
+    let exemption = FeeExemption::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    
+    Ok(exemption.discount_bps)
+}
+
+/// Whether a `RegisteredPool` passed as an unchecked account exists.
+/// The caller is responsible for checking the account's address.
+pub fn is_registered_pool(account: &AccountInfo) -> bool {
+    account.owner == &crate::ID && !account.data_is_empty()
+}
+
+/// Move tokens of the protocol mint with `transfer_checked`.
+///
+/// Any transfer-hook accounts the mint requires are looked up in
+/// `remaining_accounts`, so callers forward `ctx.remaining_accounts` as-is.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_tokens<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    invoke_transfer_checked(
+        token_program.key,
+        from.clone(),
+        mint.to_account_info(),
+        to.clone(),
+        authority.clone(),
+        remaining_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+    
+    Ok(())
+}
+
+/// Leaf of a reward epoch Merkle tree.
+/// Prefixed with 0x00 so a leaf can never be passed off as an inner node.
+pub fn epoch_leaf(index: u32, holder: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[&[0u8], &index.to_le_bytes(), holder.as_ref(), &amount.to_le_bytes()]).0
+}
+
+/// Check a Merkle proof for `leaf` against `root`.
+/// Inner nodes hash 0x01 followed by the sorted pair of children.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        keccak::hashv(&[&[1u8], &left, &right]).0
+    });
+    
+    computed == *root
+}
+
+/// Reload `vault` and fail unless it holds at least the `accounted` tokens.
+/// A surplus is allowed, so a donation can't block the protocol; `sync_pools` sweeps it.
+pub fn assert_vault_backed(
+    vault: &mut InterfaceAccount<'_, TokenAccount>,
+    accounted: u64,
+) -> Result<()> {
+    vault.reload()?;
+    require!(vault.amount >= accounted, MineballError::PoolBalanceMismatch);
+    
+    Ok(())
+}
+
+/// Check all three pool vaults against the state's counters
+pub fn assert_pools_backed(
+    state: &MineballState,
+    reward_pool: &mut InterfaceAccount<'_, TokenAccount>,
+    buyback_pool: &mut InterfaceAccount<'_, TokenAccount>,
+    liquidity_pool: &mut InterfaceAccount<'_, TokenAccount>,
+) -> Result<()> {
+    assert_vault_backed(reward_pool, state.reward_vault_liability()?)?;
+    assert_vault_backed(buyback_pool, state.buyback_vault_liability()?)?;
+    assert_vault_backed(liquidity_pool, state.liquidity_pool)
+}
@@ -0,0 +1,394 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self, spl_token_2022::onchain::invoke_transfer_checked, Mint, MintTo, TokenAccount,
+    TokenInterface,
+};
+
+declare_id!("MockAmm111111111111111111111111111111111111");
+
+pub const POOL_SEED: &[u8] = b"pool";
+pub const LP_MINT_SEED: &[u8] = b"lp-mint";
+
+/// Basis points denominator for the swap fee
+const BASIS_POINTS: u128 = 10_000;
+
+/// Highest swap fee a pool can be created with (10%)
+const MAX_FEE_BPS: u16 = 1_000;
+
+#[program]
+pub mod mock_amm {
+    use super::*;
+
+    /// Create a pool over two existing vaults owned by the pool PDA
+    /// Liquidity can be seeded by transferring straight into the vaults
+    pub fn initialize_pool(ctx: Context<InitializePool>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, AmmError::InvalidFee);
+        require_keys_neq!(
+            ctx.accounts.mint_a.key(),
+            ctx.accounts.mint_b.key(),
+            AmmError::InvalidMint
+        );
+
+        let pool = &mut ctx.accounts.pool;
+        pool.mint_a = ctx.accounts.mint_a.key();
+        pool.mint_b = ctx.accounts.mint_b.key();
+        pool.vault_a = ctx.accounts.vault_a.key();
+        pool.vault_b = ctx.accounts.vault_b.key();
+        pool.lp_mint = ctx.accounts.lp_mint.key();
+        pool.fee_bps = fee_bps;
+        pool.bump = ctx.bumps.pool;
+
+        msg!("Pool initialized with {}bps fee", fee_bps);
+
+        Ok(())
+    }
+
+    /// Swap `amount_in` of `mint_in` for at least `min_amount_out` of `mint_out`
+    /// Prices against the vault balances with `x * y = k`
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        require!(amount_in > 0, AmmError::InvalidAmount);
+
+        let pool = &ctx.accounts.pool;
+        let (expected_vault_in, expected_vault_out) = if ctx.accounts.mint_in.key() == pool.mint_a {
+            (pool.vault_a, pool.vault_b)
+        } else {
+            (pool.vault_b, pool.vault_a)
+        };
+        require_keys_eq!(ctx.accounts.vault_in.key(), expected_vault_in, AmmError::InvalidVault);
+        require_keys_eq!(ctx.accounts.vault_out.key(), expected_vault_out, AmmError::InvalidVault);
+
+        let reserve_in = ctx.accounts.vault_in.amount;
+        let reserve_out = ctx.accounts.vault_out.amount;
+
+        invoke_transfer_checked(
+            ctx.accounts.token_program_in.key,
+            ctx.accounts.source.to_account_info(),
+            ctx.accounts.mint_in.to_account_info(),
+            ctx.accounts.vault_in.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount_in,
+            ctx.accounts.mint_in.decimals,
+            &[],
+        )?;
+
+        // Price on what actually arrived, so fee-on-transfer mints can't skew the curve
+        ctx.accounts.vault_in.reload()?;
+        let received = ctx.accounts.vault_in.amount
+            .checked_sub(reserve_in)
+            .ok_or(AmmError::MathOverflow)?;
+
+        let amount_out = quote_amount_out(received, reserve_in, reserve_out, pool.fee_bps)?;
+        require!(amount_out >= min_amount_out, AmmError::SlippageExceeded);
+
+        let mint_a = pool.mint_a;
+        let mint_b = pool.mint_b;
+        let pool_seeds: &[&[u8]] = &[POOL_SEED, mint_a.as_ref(), mint_b.as_ref(), &[pool.bump]];
+
+        invoke_transfer_checked(
+            ctx.accounts.token_program_out.key,
+            ctx.accounts.vault_out.to_account_info(),
+            ctx.accounts.mint_out.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.remaining_accounts,
+            amount_out,
+            ctx.accounts.mint_out.decimals,
+            &[pool_seeds],
+        )?;
+
+        msg!("Swapped {} for {}", received, amount_out);
+
+        Ok(())
+    }
+
+    /// Deposit up to `amount_x` and `amount_y` at the pool ratio and mint LP tokens
+    /// Whatever does not fit the ratio stays with the depositor
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, Deposit<'info>>,
+        amount_x: u64,
+        amount_y: u64,
+        min_lp_out: u64,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let (expected_vault_x, expected_vault_y) = if ctx.accounts.mint_x.key() == pool.mint_a {
+            (pool.vault_a, pool.vault_b)
+        } else {
+            (pool.vault_b, pool.vault_a)
+        };
+        require_keys_eq!(ctx.accounts.vault_x.key(), expected_vault_x, AmmError::InvalidVault);
+        require_keys_eq!(ctx.accounts.vault_y.key(), expected_vault_y, AmmError::InvalidVault);
+
+        let (lp_amount, used_x, used_y) = quote_deposit(
+            amount_x,
+            amount_y,
+            ctx.accounts.vault_x.amount,
+            ctx.accounts.vault_y.amount,
+            ctx.accounts.lp_mint.supply,
+        )?;
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(lp_amount >= min_lp_out, AmmError::SlippageExceeded);
+
+        invoke_transfer_checked(
+            ctx.accounts.token_program_x.key,
+            ctx.accounts.source_x.to_account_info(),
+            ctx.accounts.mint_x.to_account_info(),
+            ctx.accounts.vault_x.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.remaining_accounts,
+            used_x,
+            ctx.accounts.mint_x.decimals,
+            &[],
+        )?;
+        invoke_transfer_checked(
+            ctx.accounts.token_program_y.key,
+            ctx.accounts.source_y.to_account_info(),
+            ctx.accounts.mint_y.to_account_info(),
+            ctx.accounts.vault_y.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.remaining_accounts,
+            used_y,
+            ctx.accounts.mint_y.decimals,
+            &[],
+        )?;
+
+        let mint_a = pool.mint_a;
+        let mint_b = pool.mint_b;
+        let pool_seeds: &[&[u8]] = &[POOL_SEED, mint_a.as_ref(), mint_b.as_ref(), &[pool.bump]];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.lp_token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.lp_destination.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                &[pool_seeds],
+            ),
+            lp_amount,
+        )?;
+
+        msg!("Deposited {} and {} for {} LP", used_x, used_y, lp_amount);
+
+        Ok(())
+    }
+}
+
+/// Output of a constant-product swap after the pool fee
+pub fn quote_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u16) -> Result<u64> {
+    let amount_in_after_fee = (amount_in as u128)
+        .checked_mul(BASIS_POINTS - fee_bps as u128)
+        .ok_or(AmmError::MathOverflow)?;
+
+    let numerator = amount_in_after_fee
+        .checked_mul(reserve_out as u128)
+        .ok_or(AmmError::MathOverflow)?;
+    let denominator = (reserve_in as u128)
+        .checked_mul(BASIS_POINTS)
+        .and_then(|v| v.checked_add(amount_in_after_fee))
+        .ok_or(AmmError::MathOverflow)?;
+
+    let amount_out = numerator
+        .checked_div(denominator)
+        .and_then(|v| u64::try_from(v).ok())
+        .ok_or(AmmError::MathOverflow)?;
+
+    Ok(amount_out)
+}
+
+/// LP minted for a deposit, and the amounts of each side it consumes
+pub fn quote_deposit(
+    amount_x: u64,
+    amount_y: u64,
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+) -> Result<(u64, u64, u64)> {
+    // First deposit sets the price
+    if lp_supply == 0 {
+        let lp_amount = isqrt((amount_x as u128) * (amount_y as u128));
+        let lp_amount = u64::try_from(lp_amount).map_err(|_| AmmError::MathOverflow)?;
+        return Ok((lp_amount, amount_x, amount_y));
+    }
+
+    require!(reserve_x > 0 && reserve_y > 0, AmmError::InvalidAmount);
+
+    let supply = lp_supply as u128;
+    let lp_x = (amount_x as u128) * supply / reserve_x as u128;
+    let lp_y = (amount_y as u128) * supply / reserve_y as u128;
+    let lp_amount = lp_x.min(lp_y);
+
+    // Round the amounts taken up so LP holders are never diluted
+    let used_x = (lp_amount * reserve_x as u128).div_ceil(supply);
+    let used_y = (lp_amount * reserve_y as u128).div_ceil(supply);
+
+    Ok((
+        u64::try_from(lp_amount).map_err(|_| AmmError::MathOverflow)?,
+        u64::try_from(used_x).map_err(|_| AmmError::MathOverflow)?.min(amount_x),
+        u64::try_from(used_y).map_err(|_| AmmError::MathOverflow)?.min(amount_y),
+    ))
+}
+
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[account]
+pub struct Pool {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_bps: u16,
+    pub bump: u8,
+}
+
+impl Pool {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 32 + 2 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Pool::LEN,
+        seeds = [POOL_SEED, mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = mint_a, token::authority = pool)]
+    pub vault_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(token::mint = mint_b, token::authority = pool)]
+    pub vault_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 9,
+        mint::authority = pool,
+        seeds = [LP_MINT_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Token program of the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.bump,
+        constraint = (mint_in.key() == pool.mint_a && mint_out.key() == pool.mint_b)
+            || (mint_in.key() == pool.mint_b && mint_out.key() == pool.mint_a)
+            @ AmmError::InvalidMint
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut, token::mint = mint_in, token::authority = authority)]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = mint_out)]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_in: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_out: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint_in: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_out: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program_in: Interface<'info, TokenInterface>,
+    pub token_program_out: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.bump,
+        constraint = (mint_x.key() == pool.mint_a && mint_y.key() == pool.mint_b)
+            || (mint_x.key() == pool.mint_b && mint_y.key() == pool.mint_a)
+            @ AmmError::InvalidMint
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut, token::mint = mint_x, token::authority = authority)]
+    pub source_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = mint_y, token::authority = authority)]
+    pub source_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.lp_mint @ AmmError::InvalidMint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = lp_mint)]
+    pub lp_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub lp_token_program: Interface<'info, TokenInterface>,
+}
+
+#[error_code]
+pub enum AmmError {
+    #[msg("Fee is above the maximum")]
+    InvalidFee,
+
+    #[msg("Mint does not belong to the pool")]
+    InvalidMint,
+
+    #[msg("Vault does not belong to the pool")]
+    InvalidVault,
+
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Output below the minimum amount")]
+    SlippageExceeded,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+}
@@ -14,11 +14,29 @@ const path = require('path');
 
 // Default configuration
 const CONFIG = {
-  taxRate: 600, // 6%
-  miningRewardShare: 4000, // 40%
-  buybackShare: 3000, // 30%
-  liquidityShare: 2000, // 20%
-  burnShare: 1000, // 10%
+  taxRates: {
+    buyTaxRate: 600, // 6%
+    sellTaxRate: 600, // 6%
+    transferTaxRate: 600, // 6%
+    // Anti-sniper launch tax, decaying to the rates above (duration 0 disables it)
+    launchTax: {
+      startRate: 2000, // 20%
+      duration: new anchor.BN(3600), // 1 hour
+      stepSeconds: new anchor.BN(600), // drops every 10 minutes
+    },
+  },
+  // Same split for every direction; sells could favour buybacks instead
+  shares: {
+    miningRewardShare: 4000, // 40%
+    buybackShare: 3000, // 30%
+    liquidityShare: 2000, // 20%
+    burnShare: 1000, // 10%
+  },
+  keeperBountyBps: 10, // 0.1% of a triggered pool to the caller
+  holderTerms: {
+    vestingDuration: new anchor.BN(7 * 24 * 3600), // claims vest over a week
+    unstakeCooldown: new anchor.BN(2 * 24 * 3600), // unstakes wait two days
+  },
 };
 
 async function main() {
@@ -90,11 +108,12 @@ async function main() {
   
   const tx = await program.methods
     .initialize(
-      CONFIG.taxRate,
-      CONFIG.miningRewardShare,
-      CONFIG.buybackShare,
-      CONFIG.liquidityShare,
-      CONFIG.burnShare
+      CONFIG.taxRates,
+      CONFIG.shares,
+      CONFIG.shares,
+      CONFIG.shares,
+      CONFIG.keeperBountyBps,
+      CONFIG.holderTerms
     )
     .accounts({
       state: statePDA,
@@ -115,11 +134,17 @@ async function main() {
   // Fetch and display state
   const state = await program.account.mineballState.fetch(statePDA);
   console.log('\n📊 Protocol State:');
-  console.log('  Tax Rate:', state.taxRate, 'bps');
-  console.log('  Mining Reward Share:', state.miningRewardShare, 'bps');
-  console.log('  Buyback Share:', state.buybackShare, 'bps');
-  console.log('  Liquidity Share:', state.liquidityShare, 'bps');
-  console.log('  Burn Share:', state.burnShare, 'bps');
+  console.log('  Buy Tax Rate:', state.buyTaxRate, 'bps');
+  console.log('  Sell Tax Rate:', state.sellTaxRate, 'bps');
+  console.log('  Transfer Tax Rate:', state.transferTaxRate, 'bps');
+  for (const direction of ['buy', 'sell', 'transfer']) {
+    const shares = state[`${direction}Shares`];
+    console.log(
+      `  ${direction} shares (reward/buyback/liquidity/burn):`,
+      `${shares.miningRewardShare}/${shares.buybackShare}/${shares.liquidityShare}/${shares.burnShare} bps`
+    );
+  }
+  console.log('  Keeper Bounty:', state.keeperBountyBps, 'bps');
 }
 
 main()
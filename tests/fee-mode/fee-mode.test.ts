@@ -0,0 +1,231 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js';
+import {
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  getAccount,
+  getMint,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  getTransferFeeAmount,
+  mintTo,
+  transferChecked,
+} from '@solana/spl-token';
+import { assert } from 'chai';
+
+// Runs on its own validator (see Test.toml): the state PDA is a singleton, and
+// this suite needs it initialized for a transfer-fee mint
+describe('mineball transfer-fee mode', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  
+  const program = anchor.workspace.Mineball as Program;
+  
+  const DECIMALS = 6;
+  const FEE_BPS = 600;
+  const STATE_SEED = 'mineball-state';
+  const REWARD_POOL_SEED = 'reward-pool';
+  const BUYBACK_POOL_SEED = 'buyback-pool';
+  const LIQUIDITY_POOL_SEED = 'liquidity-pool';
+  
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  
+  const statePDA = pda(Buffer.from(STATE_SEED));
+  const rewardPoolPDA = pda(Buffer.from(REWARD_POOL_SEED));
+  const buybackPoolPDA = pda(Buffer.from(BUYBACK_POOL_SEED));
+  const liquidityPoolPDA = pda(Buffer.from(LIQUIDITY_POOL_SEED));
+  
+  // The mint's fee is the rate of every direction, split 40/30/20/10
+  const taxRates = {
+    buyTaxRate: FEE_BPS,
+    sellTaxRate: FEE_BPS,
+    transferTaxRate: FEE_BPS,
+    launchTax: {
+      startRate: 0,
+      duration: new anchor.BN(0),
+      stepSeconds: new anchor.BN(0),
+    },
+  };
+  const shares = {
+    miningRewardShare: 4000,
+    buybackShare: 3000,
+    liquidityShare: 2000,
+    burnShare: 1000,
+  };
+  const holderTerms = {
+    vestingDuration: new anchor.BN(0),
+    unstakeCooldown: new anchor.BN(0),
+  };
+  
+  const authority = Keypair.generate();
+  const mintKeypair = Keypair.generate();
+  const mint = mintKeypair.publicKey;
+  const alice = Keypair.generate();
+  const bob = Keypair.generate();
+  let aliceAta: PublicKey;
+  let bobAta: PublicKey;
+  
+  const createAta = async (owner: PublicKey) => (
+    await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      owner,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    )
+  ).address;
+  const tokenAccount = (address: PublicKey) =>
+    getAccount(provider.connection, address, undefined, TOKEN_2022_PROGRAM_ID);
+  const balance = async (address: PublicKey) => Number((await tokenAccount(address)).amount);
+  const withheld = async (address: PublicKey) =>
+    Number(getTransferFeeAmount(await tokenAccount(address))?.withheldAmount ?? 0);
+  const supply = async () =>
+    Number((await getMint(provider.connection, mint, undefined, TOKEN_2022_PROGRAM_ID)).supply);
+  
+  const harvest = (sources: PublicKey[]) => program.methods
+    .harvestAndSplit()
+    .accounts({
+      state: statePDA,
+      mint,
+      rewardPool: rewardPoolPDA,
+      buybackPool: buybackPoolPDA,
+      liquidityPool: liquidityPoolPDA,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+    })
+    .remainingAccounts(sources.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+    .rpc();
+  
+  before(async () => {
+    const signature = await provider.connection.requestAirdrop(
+      authority.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    
+    // Token-2022 mint whose withheld fees only the state PDA can withdraw,
+    // which is what makes initialize pick transfer-fee mode
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: authority.publicKey,
+          newAccountPubkey: mint,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mint,
+          authority.publicKey,
+          statePDA,
+          FEE_BPS,
+          BigInt('18446744073709551615'),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(mint, DECIMALS, authority.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [authority, mintKeypair]
+    );
+  });
+  
+  it('Initializes in transfer-fee mode', async () => {
+    await program.methods
+      .initialize(taxRates, shares, shares, shares, 10, holderTerms)
+      .accounts({
+        state: statePDA,
+        authority: authority.publicKey,
+        mint,
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.deepEqual(state.taxMode, { transferFee: {} });
+    
+    aliceAta = await createAta(alice.publicKey);
+    bobAta = await createAta(bob.publicKey);
+    await mintTo(provider.connection, authority, mint, aliceAta, authority, 10_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+  });
+  
+  it('Harvests withheld fees and splits them four ways', async () => {
+    // Any transfer pays the mint's 6%, withheld in the recipient's account
+    await transferChecked(
+      provider.connection,
+      authority,
+      aliceAta,
+      mint,
+      bobAta,
+      alice,
+      1_000_000,
+      DECIMALS,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.equal(await balance(bobAta), 940_000);
+    assert.equal(await withheld(bobAta), 60_000);
+    
+    const supplyBefore = await supply();
+    await harvest([bobAta]);
+    assert.equal(await withheld(bobAta), 0);
+    
+    // 40% stays in the reward pool and 10% is burned out of it
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.miningRewardPool.toNumber(), 24_000);
+    assert.equal(await balance(rewardPoolPDA), 24_000);
+    assert.equal(state.totalBurned.toNumber(), 6_000);
+    assert.equal(await supply(), supplyBefore - 6_000);
+    assert.equal(state.totalSupply.toNumber(), supplyBefore - 6_000);
+    
+    // The 30% and 20% shares pay the fee again on the way to their pools,
+    // so only what arrived is credited and the rest is withheld for the next harvest
+    assert.equal(state.buybackPool.toNumber(), 18_000 - 1_080);
+    assert.equal(await balance(buybackPoolPDA), 18_000 - 1_080);
+    assert.equal(await withheld(buybackPoolPDA), 1_080);
+    assert.equal(state.liquidityPool.toNumber(), 12_000 - 720);
+    assert.equal(await balance(liquidityPoolPDA), 12_000 - 720);
+    assert.equal(await withheld(liquidityPoolPDA), 720);
+  });
+  
+  it('Refuses a harvest with nothing withheld', async () => {
+    try {
+      await harvest([bobAta]);
+      assert.fail('Empty harvest succeeded');
+    } catch (error) {
+      assert.include(error.message, 'NothingToHarvest');
+    }
+  });
+  
+  it('Refuses trading limits the native fee path cannot enforce', async () => {
+    try {
+      await program.methods
+        .setTradingLimits(new anchor.BN(200_000), new anchor.BN(0), new anchor.BN(0))
+        .accounts({ state: statePDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      assert.fail('Trading limits were set in transfer-fee mode');
+    } catch (error) {
+      assert.include(error.message, 'InvalidTaxMode');
+    }
+  });
+});
@@ -0,0 +1,447 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import {
+  AccountMeta,
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js';
+import {
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  burnChecked,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeTransferHookInstruction,
+  createTransferCheckedWithTransferHookInstruction,
+  getAccount,
+  getMint,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from '@solana/spl-token';
+import { assert } from 'chai';
+
+// Runs on its own validator (see Test.toml): the state PDA is a singleton, and
+// this suite needs it initialized for a transfer-hook mint
+describe('mineball transfer-hook mode', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  
+  const program = anchor.workspace.Mineball as Program;
+  const hookProgram = anchor.workspace.MineballHook as Program;
+  
+  const DECIMALS = 6;
+  const STATE_SEED = 'mineball-state';
+  const REWARD_POOL_SEED = 'reward-pool';
+  const BUYBACK_POOL_SEED = 'buyback-pool';
+  const LIQUIDITY_POOL_SEED = 'liquidity-pool';
+  const REGISTERED_POOL_SEED = 'registered-pool';
+  const TAX_LEDGER_SEED = 'tax-ledger';
+  const FEE_EXEMPTION_SEED = 'fee-exemption';
+  const EXTRA_ACCOUNT_METAS_SEED = 'extra-account-metas';
+  
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const ledgerPDA = (tokenAccount: PublicKey) => pda(Buffer.from(TAX_LEDGER_SEED), tokenAccount.toBuffer());
+  const poolPDA = (tokenAccount: PublicKey) => pda(Buffer.from(REGISTERED_POOL_SEED), tokenAccount.toBuffer());
+  const exemptionPDA = (owner: PublicKey) => pda(Buffer.from(FEE_EXEMPTION_SEED), owner.toBuffer());
+  
+  const statePDA = pda(Buffer.from(STATE_SEED));
+  const rewardPoolPDA = pda(Buffer.from(REWARD_POOL_SEED));
+  const buybackPoolPDA = pda(Buffer.from(BUYBACK_POOL_SEED));
+  const liquidityPoolPDA = pda(Buffer.from(LIQUIDITY_POOL_SEED));
+  
+  // 3% buys, 9% sells, 6% transfers, every direction split 40/30/20/10
+  const taxRates = {
+    buyTaxRate: 300,
+    sellTaxRate: 900,
+    transferTaxRate: 600,
+    launchTax: {
+      startRate: 0,
+      duration: new anchor.BN(0),
+      stepSeconds: new anchor.BN(0),
+    },
+  };
+  const shares = {
+    miningRewardShare: 4000,
+    buybackShare: 3000,
+    liquidityShare: 2000,
+    burnShare: 1000,
+  };
+  const holderTerms = {
+    vestingDuration: new anchor.BN(0),
+    unstakeCooldown: new anchor.BN(0),
+  };
+  
+  const authority = Keypair.generate();
+  const mintKeypair = Keypair.generate();
+  const mint = mintKeypair.publicKey;
+  const alice = Keypair.generate();
+  const bob = Keypair.generate();
+  const ammOwner = Keypair.generate();
+  let extraAccountMetaList: PublicKey;
+  let aliceAta: PublicKey;
+  let bobAta: PublicKey;
+  let poolAta: PublicKey;
+  
+  const createAta = async (owner: PublicKey) => (
+    await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      owner,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    )
+  ).address;
+  const balance = async (tokenAccount: PublicKey) =>
+    Number((await getAccount(provider.connection, tokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).amount);
+  const supply = async () =>
+    Number((await getMint(provider.connection, mint, undefined, TOKEN_2022_PROGRAM_ID)).supply);
+  const ledger = (tokenAccount: PublicKey) => program.account.taxLedger.fetch(ledgerPDA(tokenAccount));
+  
+  // A wallet-signed transfer, with the hook's extra accounts resolved from the meta list
+  const transfer = async (from: PublicKey, to: PublicKey, owner: Keypair, amount: number) => {
+    const instruction = await createTransferCheckedWithTransferHookInstruction(
+      provider.connection,
+      from,
+      mint,
+      to,
+      owner.publicKey,
+      BigInt(amount),
+      DECIMALS,
+      [],
+      'confirmed',
+      TOKEN_2022_PROGRAM_ID
+    );
+    await sendAndConfirmTransaction(provider.connection, new Transaction().add(instruction), [owner]);
+  };
+  
+  const openLedger = (tokenAccount: PublicKey) => program.methods
+    .initializeTaxLedger()
+    .accounts({
+      state: statePDA,
+      taxLedger: ledgerPDA(tokenAccount),
+      tokenAccount,
+      payer: authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+  
+  // collect_hook_tax moves tokens out of `tokenAccount`, so Token-2022 needs the
+  // hook's extra accounts for a transfer from it into each of the three pools
+  const collect = (tokenAccount: PublicKey, owner: PublicKey) => {
+    const meta = (pubkey: PublicKey, isWritable = false): AccountMeta => ({
+      pubkey,
+      isSigner: false,
+      isWritable,
+    });
+    const remainingAccounts = [
+      meta(hookProgram.programId),
+      meta(extraAccountMetaList),
+      meta(program.programId),
+      meta(exemptionPDA(owner)),
+      meta(exemptionPDA(statePDA)),
+      meta(poolPDA(tokenAccount)),
+    ];
+    // The hook marks tax ledgers writable
+    for (const pool of [rewardPoolPDA, buybackPoolPDA, liquidityPoolPDA]) {
+      remainingAccounts.push(meta(ledgerPDA(pool), true), meta(poolPDA(pool)));
+    }
+    
+    return program.methods
+      .collectHookTax()
+      .accounts({
+        state: statePDA,
+        mint,
+        tokenAccount,
+        taxLedger: ledgerPDA(tokenAccount),
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+  };
+  
+  const pools = async () => [
+    await balance(rewardPoolPDA),
+    await balance(buybackPoolPDA),
+    await balance(liquidityPoolPDA),
+  ];
+  
+  before(async () => {
+    const signature = await provider.connection.requestAirdrop(
+      authority.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    
+    // Token-2022 mint whose hook is mineball_hook and whose permanent delegate
+    // is the state PDA, which is what makes initialize pick transfer-hook mode
+    const mintLen = getMintLen([ExtensionType.TransferHook, ExtensionType.PermanentDelegate]);
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: authority.publicKey,
+          newAccountPubkey: mint,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          mint,
+          authority.publicKey,
+          hookProgram.programId,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializePermanentDelegateInstruction(mint, statePDA, TOKEN_2022_PROGRAM_ID),
+        createInitializeMintInstruction(mint, DECIMALS, authority.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [authority, mintKeypair]
+    );
+    
+    [extraAccountMetaList] = PublicKey.findProgramAddressSync(
+      [Buffer.from(EXTRA_ACCOUNT_METAS_SEED), mint.toBuffer()],
+      hookProgram.programId
+    );
+  });
+  
+  it('Initializes in transfer-hook mode', async () => {
+    await program.methods
+      .initialize(taxRates, shares, shares, shares, 10, holderTerms)
+      .accounts({
+        state: statePDA,
+        authority: authority.publicKey,
+        mint,
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([authority])
+      .rpc();
+    
+    await hookProgram.methods
+      .initializeExtraAccountMetaList()
+      .accounts({
+        payer: authority.publicKey,
+        extraAccountMetaList,
+        mint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.deepEqual(state.taxMode, { transferHook: {} });
+    
+    aliceAta = await createAta(alice.publicKey);
+    bobAta = await createAta(bob.publicKey);
+    poolAta = await createAta(ammOwner.publicKey);
+    
+    // Minting never runs the hook
+    await mintTo(provider.connection, authority, mint, aliceAta, authority, 10_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+    await mintTo(provider.connection, authority, mint, poolAta, authority, 100_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+    
+    await program.methods
+      .registerPool()
+      .accounts({
+        state: statePDA,
+        registeredPool: poolPDA(poolAta),
+        poolTaxLedger: ledgerPDA(poolAta),
+        poolTokenAccount: poolAta,
+        mint,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await openLedger(aliceAta);
+    
+    // register_pool opens the pool's ledger
+    assert.equal((await ledger(poolAta)).taxOwed.toNumber(), 0);
+  });
+  
+  it('Records the tax of wallet transfers, buys and sells', async () => {
+    // Transfer: 6% of 1_000_000, charged to alice as the sender
+    await transfer(aliceAta, bobAta, alice, 1_000_000);
+    assert.equal(await balance(bobAta), 1_000_000);
+    assert.equal((await ledger(aliceAta)).taxOwed.toNumber(), 60_000);
+    
+    // Buy into bob's account, which has no ledger: 3% of 2_000_000 charged to the pool
+    await transfer(poolAta, bobAta, ammOwner, 2_000_000);
+    assert.equal(await balance(bobAta), 3_000_000);
+    assert.equal((await ledger(poolAta)).taxOwed.toNumber(), 60_000);
+    
+    // Sell from alice, who has a ledger: 9% of 1_000_000 charged to her
+    await transfer(aliceAta, poolAta, alice, 1_000_000);
+    const aliceLedger = await ledger(aliceAta);
+    assert.equal(aliceLedger.taxOwed.toNumber(), 150_000);
+    assert.equal(aliceLedger.owedSplit.miningRewardAmount.toNumber(), 60_000);
+    assert.equal(aliceLedger.owedSplit.buybackAmount.toNumber(), 45_000);
+    assert.equal(aliceLedger.owedSplit.liquidityAmount.toNumber(), 30_000);
+    assert.equal(aliceLedger.owedSplit.burnAmount.toNumber(), 15_000);
+    
+    // Sell from bob, who has none: 9% of 500_000 charged to the pool
+    await transfer(bobAta, poolAta, bob, 500_000);
+    assert.equal((await ledger(poolAta)).taxOwed.toNumber(), 105_000);
+    assert.equal(await balance(poolAta), 100_000_000 - 2_000_000 + 1_000_000 + 500_000);
+    
+    // A wallet transfer still needs the sender's ledger
+    try {
+      await transfer(bobAta, aliceAta, bob, 100_000);
+      assert.fail('Transfer without a sender ledger was accepted');
+    } catch (error) {
+      assert.include(String(error.logs ?? error.message), 'TaxLedgerRequired');
+    }
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.totalVolume.toNumber(), 4_500_000);
+  });
+  
+  it('Collects recorded tax into the pools', async () => {
+    const [rewardBefore, buybackBefore, liquidityBefore] = await pools();
+    const supplyBefore = await supply();
+    const aliceBefore = await balance(aliceAta);
+    
+    await collect(aliceAta, alice.publicKey);
+    
+    assert.equal(await balance(aliceAta), aliceBefore - 150_000);
+    assert.deepEqual(await pools(), [
+      rewardBefore + 60_000,
+      buybackBefore + 45_000,
+      liquidityBefore + 30_000,
+    ]);
+    assert.equal(await supply(), supplyBefore - 15_000);
+    
+    const aliceLedger = await ledger(aliceAta);
+    assert.equal(aliceLedger.taxOwed.toNumber(), 0);
+    assert.equal(aliceLedger.totalTaxPaid.toNumber(), 150_000);
+    
+    // The pool's ledger is paid out of its reserves
+    const poolBefore = await balance(poolAta);
+    await collect(poolAta, ammOwner.publicKey);
+    assert.equal(await balance(poolAta), poolBefore - 105_000);
+    assert.equal((await ledger(poolAta)).taxOwed.toNumber(), 0);
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.miningRewardPool.toNumber(), 60_000 + 42_000);
+    assert.equal(state.buybackPool.toNumber(), 45_000 + 31_500);
+    assert.equal(state.liquidityPool.toNumber(), 30_000 + 21_000);
+    assert.equal(state.totalBurned.toNumber(), 15_000 + 10_500);
+  });
+  
+  it('Writes off tax the balance no longer covers', async () => {
+    // 6% of 100_000 recorded against bob, who then burns all but 3_000 tokens
+    await openLedger(bobAta);
+    await transfer(bobAta, aliceAta, bob, 100_000);
+    assert.equal((await ledger(bobAta)).taxOwed.toNumber(), 6_000);
+    
+    const bobBalance = await balance(bobAta);
+    await burnChecked(
+      provider.connection,
+      authority,
+      bobAta,
+      mint,
+      bob,
+      bobBalance - 3_000,
+      DECIMALS,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    
+    const [rewardBefore, buybackBefore, liquidityBefore] = await pools();
+    await collect(bobAta, bob.publicKey);
+    
+    // Half the debt is collected with half of each share, the rest written off
+    assert.equal(await balance(bobAta), 0);
+    assert.deepEqual(await pools(), [
+      rewardBefore + 1_200,
+      buybackBefore + 900,
+      liquidityBefore + 600,
+    ]);
+    
+    const bobLedger = await ledger(bobAta);
+    assert.equal(bobLedger.taxOwed.toNumber(), 0);
+    assert.equal(bobLedger.totalTaxPaid.toNumber(), 3_000);
+  });
+  
+  it('Enforces the trading limits in the hook', async () => {
+    const setLimits = (maxTx: number, maxWallet: number, cooldown: number) => program.methods
+      .setTradingLimits(new anchor.BN(maxTx), new anchor.BN(maxWallet), new anchor.BN(cooldown))
+      .accounts({ state: statePDA, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    const expectFailure = async (send: Promise<unknown>, code: string) => {
+      try {
+        await send;
+        assert.fail(`Expected ${code}`);
+      } catch (error) {
+        assert.include(String(error.logs ?? error.message), code);
+      }
+    };
+    
+    // The hook has nowhere to track a wallet's last sell
+    await expectFailure(setLimits(0, 0, 60), 'InvalidTaxMode');
+    
+    await setLimits(200_000, 200_000, 0);
+    
+    // Max tx applies to sells into a pool, max wallet to the recipient's balance
+    await expectFailure(transfer(aliceAta, poolAta, alice, 300_000), 'MaxTransactionExceeded');
+    await transfer(aliceAta, bobAta, alice, 150_000);
+    await expectFailure(transfer(aliceAta, bobAta, alice, 100_000), 'MaxWalletExceeded');
+    
+    // The pool itself has no max wallet
+    await transfer(aliceAta, poolAta, alice, 200_000);
+    
+    await program.methods
+      .liftTradingLimits()
+      .accounts({ state: statePDA, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await transfer(aliceAta, bobAta, alice, 100_000);
+    assert.equal(await balance(bobAta), 250_000);
+  });
+  
+  it('Freezes hook transfers while transfers are paused', async () => {
+    const PAUSE_TRANSFERS = 1 << 0;
+    
+    await program.methods
+      .setGuardian(authority.publicKey)
+      .accounts({ state: statePDA, caller: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .pause(PAUSE_TRANSFERS)
+      .accounts({ state: statePDA, guardian: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    
+    try {
+      await transfer(aliceAta, bobAta, alice, 1_000);
+      assert.fail('Transfer went through while paused');
+    } catch (error) {
+      assert.include(String(error.logs ?? error.message), 'ProtocolPaused');
+    }
+    
+    await program.methods
+      .unpause(PAUSE_TRANSFERS)
+      .accounts({ state: statePDA, caller: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await transfer(aliceAta, bobAta, alice, 1_000);
+  });
+});
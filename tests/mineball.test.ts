@@ -1,7 +1,16 @@
 import * as anchor from '@project-serum/anchor';
 import { Program } from '@project-serum/anchor';
 import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID, createMint, mintTo, getOrCreateAssociatedTokenAccount } from '@solana/spl-token';
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getAccount,
+  getMint,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+} from '@solana/spl-token';
+import { keccak_256 } from '@noble/hashes/sha3';
 import { assert } from 'chai';
 
 describe('mineball', () => {
@@ -9,6 +18,7 @@ describe('mineball', () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Mineball as Program;
+  const ammProgram = anchor.workspace.MockAmm as Program;
   
   let mint: PublicKey;
   let authority: Keypair;
@@ -21,6 +31,17 @@ describe('mineball', () => {
   const REWARD_POOL_SEED = 'reward-pool';
   const BUYBACK_POOL_SEED = 'buyback-pool';
   const LIQUIDITY_POOL_SEED = 'liquidity-pool';
+  const BUYBACK_VAULT_SEED = 'buyback-vault';
+  const LIQUIDITY_QUOTE_VAULT_SEED = 'liquidity-quote-vault';
+  const LP_VAULT_SEED = 'lp-vault';
+  const KEEPER_REGISTRY_SEED = 'keeper-registry';
+  const KEEPER_SEED = 'keeper';
+  const MINING_CYCLE_SEED = 'mining-cycle';
+  const REGISTERED_POOL_SEED = 'registered-pool';
+  const STAKE_SEED = 'stake';
+  const STAKE_VAULT_SEED = 'stake-vault';
+  const UNSTAKE_COOLDOWN = 2;
+  const VESTING_DURATION = 6;
   
   before(async () => {
     authority = Keypair.generate();
@@ -64,19 +85,51 @@ describe('mineball', () => {
   });
   
   it('Initializes the protocol', async () => {
-    const taxRate = 600; // 6%
-    const miningRewardShare = 4000; // 40%
-    const buybackShare = 3000; // 30%
-    const liquidityShare = 2000; // 20%
-    const burnShare = 1000; // 10%
+    const taxRates = {
+      buyTaxRate: 300, // 3%
+      sellTaxRate: 900, // 9%
+      transferTaxRate: 600, // 6%
+      // 20% at launch, decaying to the rates above over an hour
+      launchTax: {
+        startRate: 2000,
+        duration: new anchor.BN(3600),
+        stepSeconds: new anchor.BN(0),
+      },
+    };
+    // Buys feed rewards, sells feed buybacks
+    const buyShares = {
+      miningRewardShare: 6000,
+      buybackShare: 1000,
+      liquidityShare: 2000,
+      burnShare: 1000,
+    };
+    const sellShares = {
+      miningRewardShare: 2000,
+      buybackShare: 5000,
+      liquidityShare: 2000,
+      burnShare: 1000,
+    };
+    const transferShares = {
+      miningRewardShare: 4000, // 40%
+      buybackShare: 3000, // 30%
+      liquidityShare: 2000, // 20%
+      burnShare: 1000, // 10%
+    };
+    const keeperBountyBps = 10; // 0.1%
+    // Claims vest and unstakes wait, both for a few seconds
+    const holderTerms = {
+      vestingDuration: new anchor.BN(VESTING_DURATION),
+      unstakeCooldown: new anchor.BN(UNSTAKE_COOLDOWN),
+    };
     
     await program.methods
       .initialize(
-        taxRate,
-        miningRewardShare,
-        buybackShare,
-        liquidityShare,
-        burnShare
+        taxRates,
+        buyShares,
+        sellShares,
+        transferShares,
+        keeperBountyBps,
+        holderTerms
       )
       .accounts({
         state: statePDA,
@@ -94,11 +147,23 @@ describe('mineball', () => {
     
     const state = await program.account.mineballState.fetch(statePDA);
     
-    assert.equal(state.taxRate, taxRate);
-    assert.equal(state.miningRewardShare, miningRewardShare);
-    assert.equal(state.buybackShare, buybackShare);
-    assert.equal(state.liquidityShare, liquidityShare);
-    assert.equal(state.burnShare, burnShare);
+    assert.equal(state.buyTaxRate, taxRates.buyTaxRate);
+    assert.equal(state.sellTaxRate, taxRates.sellTaxRate);
+    assert.equal(state.transferTaxRate, taxRates.transferTaxRate);
+    assert.deepEqual(state.buyShares, buyShares);
+    assert.deepEqual(state.sellShares, sellShares);
+    assert.deepEqual(state.transferShares, transferShares);
+    assert.equal(state.keeperBountyBps, keeperBountyBps);
+    assert.equal(state.vestingDuration.toNumber(), VESTING_DURATION);
+    assert.equal(state.unstakeCooldown.toNumber(), UNSTAKE_COOLDOWN);
+    
+    // The launch tax is still close to its starting rate
+    const effectiveRate = await program.methods
+      .getEffectiveTaxRate({ transfer: {} })
+      .accounts({ state: statePDA })
+      .view();
+    assert(effectiveRate > taxRates.transferTaxRate);
+    assert(effectiveRate <= taxRates.launchTax.startRate);
   });
   
   it('Executes a transfer with tax', async () => {
@@ -141,6 +206,18 @@ describe('mineball', () => {
       mintAmount
     );
     
+    // Sender's holder info PDA, which tracks the sell cooldown
+    const [fromHolderInfoPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('holder'), sender.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    // Pool registrations of both token accounts, always passed so the direction can't be hidden
+    const poolPDA = (tokenAccount: PublicKey) => PublicKey.findProgramAddressSync(
+      [Buffer.from(REGISTERED_POOL_SEED), tokenAccount.toBuffer()],
+      program.programId
+    )[0];
+    
     // Execute transfer with tax
     const transferAmount = 100000; // 0.1 token
     
@@ -151,9 +228,16 @@ describe('mineball', () => {
         from: sender.publicKey,
         fromTokenAccount: senderAta.address,
         toTokenAccount: recipientAta.address,
+        fromHolderInfo: fromHolderInfoPDA,
+        fromExemption: null,
+        toExemption: null,
+        fromPool: poolPDA(senderAta.address),
+        toPool: poolPDA(recipientAta.address),
         rewardPool: rewardPoolPDA,
         buybackPool: buybackPoolPDA,
         liquidityPool: liquidityPoolPDA,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([sender])
@@ -166,6 +250,442 @@ describe('mineball', () => {
     assert(state.totalVolume.toNumber() === transferAmount);
   });
   
+  it('Executes a buyback through the AMM', async () => {
+    // Quote mint standing in for wrapped SOL
+    const quoteMint = await createMint(
+      provider.connection,
+      authority,
+      authority.publicKey,
+      null,
+      9
+    );
+    
+    // Mock AMM pool with vaults owned by the pool PDA
+    const [ammPoolPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('pool'), quoteMint.toBuffer(), mint.toBuffer()],
+      ammProgram.programId
+    );
+    const [ammLpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from('lp-mint'), ammPoolPDA.toBuffer()],
+      ammProgram.programId
+    );
+    const ammQuoteVault = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      quoteMint,
+      ammPoolPDA,
+      true
+    );
+    const ammTokenVault = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      ammPoolPDA,
+      true
+    );
+    
+    await ammProgram.methods
+      .initializePool(30)
+      .accounts({
+        pool: ammPoolPDA,
+        mintA: quoteMint,
+        mintB: mint,
+        vaultA: ammQuoteVault.address,
+        vaultB: ammTokenVault.address,
+        lpMint: ammLpMint,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    // Seed liquidity
+    await mintTo(provider.connection, authority, quoteMint, ammQuoteVault.address, authority, 10_000_000_000_000);
+    await mintTo(provider.connection, authority, mint, ammTokenVault.address, authority, 10_000_000_000_000);
+    
+    const [buybackVaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(BUYBACK_VAULT_SEED)],
+      program.programId
+    );
+    
+    await program.methods
+      // Threshold in quote units, since it is compared against the quote vault
+      .initializeBuybackVault(true, new anchor.BN(500_000_000_000))
+      .accounts({
+        state: statePDA,
+        authority: authority.publicKey,
+        quoteMint: quoteMint,
+        buybackVault: buybackVaultPDA,
+        ammProgram: ammProgram.programId,
+        ammPool: ammPoolPDA,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    // Register the authority as a keeper
+    const [keeperRegistryPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(KEEPER_REGISTRY_SEED)],
+      program.programId
+    );
+    const [keeperInfoPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(KEEPER_SEED), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    await program.methods
+      .initializeKeeperRegistry({
+        permissioned: true,
+        minBond: new anchor.BN(0),
+        maxSlippageBps: 500,
+        slashBps: 1000,
+        cooldownSeconds: new anchor.BN(3600),
+        // Quote per token scaled by 1e12; the seeded AMM pool trades 1:1
+        referencePrice: new anchor.BN('1000000000000'),
+      })
+      .accounts({
+        state: statePDA,
+        keeperRegistry: keeperRegistryPDA,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    await program.methods
+      .registerKeeper(new anchor.BN(0))
+      .accounts({
+        state: statePDA,
+        keeperRegistry: keeperRegistryPDA,
+        keeperInfo: keeperInfoPDA,
+        keeper: authority.publicKey,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    // The reference price was just seeded, so it can't be observed again yet
+    try {
+      await program.methods
+        .observePrice()
+        .accounts({
+          state: statePDA,
+          keeperRegistry: keeperRegistryPDA,
+          mint: mint,
+          quoteMint: quoteMint,
+          ammPool: ammPoolPDA,
+          ammQuoteVault: ammQuoteVault.address,
+          ammTokenVault: ammTokenVault.address,
+        })
+        .rpc();
+      assert.fail('Reference price was observed twice in one interval');
+    } catch (error) {
+      assert.include(error.message, 'PriceObservationTooSoon');
+    }
+    
+    const executorTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      authority.publicKey
+    );
+    const deadline = Math.floor(Date.now() / 1000) + 60;
+    
+    // The buyback share of the tax collected so far waits in the buyback pool
+    const taxShare = (await program.account.mineballState.fetch(statePDA)).buybackPool.toNumber();
+    assert(taxShare > 0);
+    
+    // The vault is funded in quote, here by the authority up to the threshold
+    const executorQuoteAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      quoteMint,
+      authority.publicKey
+    );
+    const buybackAmount = 500_000_000_000;
+    await mintTo(provider.connection, authority, quoteMint, executorQuoteAccount.address, authority, buybackAmount);
+    
+    await program.methods
+      .depositBuyback(new anchor.BN(buybackAmount))
+      .accounts({
+        state: statePDA,
+        buybackVault: buybackVaultPDA,
+        quoteMint: quoteMint,
+        depositor: authority.publicKey,
+        depositorQuoteAccount: executorQuoteAccount.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const funded = await getAccount(provider.connection, buybackVaultPDA);
+    assert.equal(Number(funded.amount), buybackAmount);
+    
+    const before = await program.account.mineballState.fetch(statePDA);
+    
+    await program.methods
+      .executeBuyback(new anchor.BN(470_000_000_000), new anchor.BN(deadline))
+      .accounts({
+        state: statePDA,
+        buybackVault: buybackVaultPDA,
+        buybackPool: buybackPoolPDA,
+        quoteMint: quoteMint,
+        mint: mint,
+        ammProgram: ammProgram.programId,
+        ammPool: ammPoolPDA,
+        ammQuoteVault: ammQuoteVault.address,
+        ammTokenVault: ammTokenVault.address,
+        keeperRegistry: keeperRegistryPDA,
+        keeperInfo: keeperInfoPDA,
+        executor: authority.publicKey,
+        executorQuoteAccount: executorQuoteAccount.address,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    
+    // The keeper was paid its bounty and the rest was spent
+    const keeperBounty = Math.floor((buybackAmount * 10) / 10_000);
+    const bountyAccount = await getAccount(provider.connection, executorQuoteAccount.address);
+    assert.equal(Number(bountyAccount.amount), keeperBounty);
+    assert.equal(state.totalBuybackExecuted.toNumber(), buybackAmount - keeperBounty);
+    
+    // The purchase was burned together with the tax share, which was never traded
+    const burned = state.totalBurned.sub(before.totalBurned).toNumber();
+    assert(burned > taxShare);
+    assert.equal(before.totalSupply.sub(state.totalSupply).toNumber(), burned);
+    assert.equal(state.buybackPool.toNumber(), 0);
+    assert.equal(state.boughtBack.toNumber(), 0);
+    
+    const keeperInfo = await program.account.keeper.fetch(keeperInfoPDA);
+    assert.equal(keeperInfo.triggersExecuted.toNumber(), 1);
+    
+    // Open the liquidity vaults so the crank can be given every account
+    const [liquidityQuoteVaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(LIQUIDITY_QUOTE_VAULT_SEED)],
+      program.programId
+    );
+    const [lpVaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(LP_VAULT_SEED)],
+      program.programId
+    );
+    
+    await program.methods
+      .initializeLpVault()
+      .accounts({
+        state: statePDA,
+        authority: authority.publicKey,
+        quoteMint: quoteMint,
+        lpMint: ammLpMint,
+        liquidityQuoteVault: liquidityQuoteVaultPDA,
+        lpVault: lpVaultPDA,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const cyclePDA = (cycle: number) => PublicKey.findProgramAddressSync(
+      [Buffer.from(MINING_CYCLE_SEED), new anchor.BN(cycle).toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    )[0];
+    
+    // The buyback emptied its vault and no other pool is ready, so the crank has nothing to do
+    try {
+      await program.methods
+        .crank({
+          minBuybackOut: new anchor.BN(0),
+          minQuoteOut: new anchor.BN(0),
+          minLpOut: new anchor.BN(0),
+          deadline: new anchor.BN(deadline),
+        })
+        .accounts({
+          state: statePDA,
+          rewardPool: rewardPoolPDA,
+          buybackVault: buybackVaultPDA,
+          buybackPool: buybackPoolPDA,
+          liquidityPool: liquidityPoolPDA,
+          liquidityQuoteVault: liquidityQuoteVaultPDA,
+          lpVault: lpVaultPDA,
+          mint: mint,
+          quoteMint: quoteMint,
+          lpMint: ammLpMint,
+          ammProgram: ammProgram.programId,
+          ammPool: ammPoolPDA,
+          ammQuoteVault: ammQuoteVault.address,
+          ammTokenVault: ammTokenVault.address,
+          keeperRegistry: keeperRegistryPDA,
+          keeperInfo: keeperInfoPDA,
+          executor: authority.publicKey,
+          executorTokenAccount: executorTokenAccount.address,
+          executorQuoteAccount: executorQuoteAccount.address,
+          currentCycle: cyclePDA(1),
+          nextCycle: cyclePDA(2),
+          quoteTokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          lpTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      assert.fail('Idle crank succeeded');
+    } catch (error) {
+      assert.include(error.message, 'NothingToCrank');
+    }
+  });
+  
+  it('Adds the liquidity pool to the AMM and keeps the LP tokens', async () => {
+    const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const lpVaultPDA = pda(Buffer.from(LP_VAULT_SEED));
+    const keeperInfoPDA = pda(Buffer.from(KEEPER_SEED), authority.publicKey.toBuffer());
+    
+    // Sells send as much to liquidity as to rewards, so the reward pool stays below its threshold
+    const seller = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(
+      seller.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdrop);
+    const sellerAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      seller.publicKey
+    );
+    const poolTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      Keypair.generate().publicKey
+    );
+    const poolPDA = (tokenAccount: PublicKey) => pda(Buffer.from(REGISTERED_POOL_SEED), tokenAccount.toBuffer());
+    
+    await program.methods
+      .registerPool()
+      .accounts({
+        state: statePDA,
+        registeredPool: poolPDA(poolTokenAccount.address),
+        poolTaxLedger: pda(Buffer.from('tax-ledger'), poolTokenAccount.address.toBuffer()),
+        poolTokenAccount: poolTokenAccount.address,
+        mint: mint,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    // Size the sell so its liquidity share fills the pool past the threshold, with room for the launch tax decaying
+    let state = await program.account.mineballState.fetch(statePDA);
+    const sellRate = await program.methods
+      .getEffectiveTaxRate({ sell: {} })
+      .accounts({ state: statePDA })
+      .view();
+    const sellAmount = state.liquidityThreshold
+      .sub(state.liquidityPool)
+      .mul(new anchor.BN(10_000 * 10_000))
+      .div(new anchor.BN(sellRate * state.sellShares.liquidityShare))
+      .muln(105)
+      .divn(100);
+    await mintTo(provider.connection, authority, mint, sellerAta.address, authority, BigInt(sellAmount.toString()));
+    
+    await program.methods
+      .transferWithTax(sellAmount)
+      .accounts({
+        state: statePDA,
+        from: seller.publicKey,
+        fromTokenAccount: sellerAta.address,
+        toTokenAccount: poolTokenAccount.address,
+        fromHolderInfo: pda(Buffer.from('holder'), seller.publicKey.toBuffer()),
+        fromExemption: null,
+        toExemption: null,
+        fromPool: poolPDA(sellerAta.address),
+        toPool: poolPDA(poolTokenAccount.address),
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([seller])
+      .rpc();
+    
+    const before = await program.account.mineballState.fetch(statePDA);
+    assert(before.liquidityPool.gte(before.liquidityThreshold));
+    assert(before.miningRewardPool.lt(before.rewardDistributionThreshold));
+    
+    // The tightest floor the registry accepts for swapping half the pool after the bounty
+    const keeperBounty = before.liquidityPool.muln(before.keeperBountyBps).divn(10_000);
+    const swapAmount = before.liquidityPool.sub(keeperBounty).divn(2);
+    const ammTokenVault = getAssociatedTokenAddressSync(mint, before.ammPool, true);
+    const tokenReserve = new anchor.BN((await getAccount(provider.connection, ammTokenVault)).amount.toString());
+    const expectedOut = swapAmount.mul(tokenReserve).div(tokenReserve.add(swapAmount));
+    const minQuoteOut = expectedOut.sub(expectedOut.muln(500).divn(10_000));
+    
+    const executorTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      authority.publicKey
+    );
+    const bountyBefore = executorTokenAccount.amount;
+    const keeperBefore = await program.account.keeper.fetch(keeperInfoPDA);
+    
+    await program.methods
+      .addLiquidity(minQuoteOut, new anchor.BN(1), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({
+        state: statePDA,
+        liquidityPool: liquidityPoolPDA,
+        liquidityQuoteVault: pda(Buffer.from(LIQUIDITY_QUOTE_VAULT_SEED)),
+        lpVault: lpVaultPDA,
+        mint: mint,
+        quoteMint: before.quoteMint,
+        lpMint: before.lpMint,
+        ammProgram: before.ammProgram,
+        ammPool: before.ammPool,
+        ammQuoteVault: getAssociatedTokenAddressSync(before.quoteMint, before.ammPool, true),
+        ammTokenVault: ammTokenVault,
+        keeperRegistry: pda(Buffer.from(KEEPER_REGISTRY_SEED)),
+        keeperInfo: keeperInfoPDA,
+        executor: authority.publicKey,
+        executorTokenAccount: executorTokenAccount.address,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    state = await program.account.mineballState.fetch(statePDA);
+    
+    // The first deposit into the AMM takes both halves whole, so the pool is spent down to nothing
+    const tokensUsed = before.liquidityPool.sub(keeperBounty);
+    assert.equal(state.totalLiquidityAdded.sub(before.totalLiquidityAdded).toString(), tokensUsed.toString());
+    assert.equal(state.liquidityPool.toNumber(), 0);
+    assert(state.lastLiquidityAdd.toNumber() > 0);
+    
+    const liquidityVault = await getAccount(provider.connection, liquidityPoolPDA);
+    assert.equal(liquidityVault.amount.toString(), state.liquidityPool.toString());
+    
+    // Every LP token the AMM minted sits in the LP vault
+    const lpVault = await getAccount(provider.connection, lpVaultPDA);
+    const lpMint = await getMint(provider.connection, before.lpMint);
+    assert(lpVault.amount > BigInt(0));
+    assert.equal(lpVault.amount.toString(), lpMint.supply.toString());
+    
+    // The keeper was paid its bounty in tokens and credited with the trigger
+    const bountyAccount = await getAccount(provider.connection, executorTokenAccount.address);
+    assert.equal((bountyAccount.amount - bountyBefore).toString(), keeperBounty.toString());
+    const keeperInfo = await program.account.keeper.fetch(keeperInfoPDA);
+    assert.equal(keeperInfo.triggersExecuted.toNumber(), keeperBefore.triggersExecuted.toNumber() + 1);
+  });
+  
   it('Claims rewards for a holder', async () => {
     const holder = Keypair.generate();
     
@@ -194,11 +714,15 @@ describe('mineball', () => {
       1000000
     );
     
-    // Derive holder PDA
+    // Derive holder PDAs
     const [holderInfoPDA] = PublicKey.findProgramAddressSync(
       [Buffer.from('holder'), holder.publicKey.toBuffer()],
       program.programId
     );
+    const [vestingSchedulePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vesting'), holder.publicKey.toBuffer()],
+      program.programId
+    );
     
     // Note: This test will fail if reward pool doesn't have sufficient balance
     // In a real scenario, you'd need to ensure the reward pool has tokens
@@ -208,6 +732,8 @@ describe('mineball', () => {
         .accounts({
           state: statePDA,
           holderInfo: holderInfoPDA,
+          // A vesting period is configured, so the claim is locked here
+          vestingSchedule: vestingSchedulePDA,
           holder: holder.publicKey,
           holderTokenAccount: holderAta.address,
           rewardPool: rewardPoolPDA,
@@ -223,4 +749,1314 @@ describe('mineball', () => {
       console.log('Expected error (no rewards in pool yet):', error.message);
     }
   });
+  
+  it('Lets the guardian pause and only the authority unpause', async () => {
+    const PAUSE_BUYBACK = 1 << 2;
+    const guardian = Keypair.generate();
+    
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accounts({ state: statePDA, caller: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    
+    await program.methods
+      .pause(PAUSE_BUYBACK)
+      .accounts({ state: statePDA, guardian: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    
+    let state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.paused, PAUSE_BUYBACK);
+    const pausedAt = state.pausedAt.toNumber();
+    
+    // Adding a group to an active pause doesn't restart its clock
+    const PAUSE_CLAIMS = 1 << 1;
+    await new Promise((resolve) => setTimeout(resolve, 1500));
+    await program.methods
+      .pause(PAUSE_CLAIMS)
+      .accounts({ state: statePDA, guardian: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    
+    state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.paused, PAUSE_BUYBACK | PAUSE_CLAIMS);
+    assert.equal(state.pausedAt.toNumber(), pausedAt);
+    
+    // The guardian can't undo its own pause
+    try {
+      await program.methods
+        .unpause(PAUSE_BUYBACK)
+        .accounts({ state: statePDA, caller: guardian.publicKey })
+        .signers([guardian])
+        .rpc();
+      assert.fail('Guardian unpaused');
+    } catch (error) {
+      assert.include(error.message, 'Unauthorized');
+    }
+    
+    await program.methods
+      .unpause(PAUSE_BUYBACK | PAUSE_CLAIMS)
+      .accounts({ state: statePDA, caller: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    
+    state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.paused, 0);
+    
+    // The guardian can step down without the authority
+    await program.methods
+      .setGuardian(PublicKey.default)
+      .accounts({ state: statePDA, caller: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    
+    state = await program.account.mineballState.fetch(statePDA);
+    assert.isTrue(state.guardian.equals(PublicKey.default));
+  });
+  
+  it('Adds and removes a fee exemption', async () => {
+    const exempt = Keypair.generate();
+    const [feeExemptionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('fee-exemption'), exempt.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    await program.methods
+      .addFeeExemption(exempt.publicKey, 10000)
+      .accounts({
+        state: statePDA,
+        feeExemption: feeExemptionPDA,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const exemption = await program.account.feeExemption.fetch(feeExemptionPDA);
+    assert(exemption.address.equals(exempt.publicKey));
+    assert.equal(exemption.discountBps, 10000);
+    
+    await program.methods
+      .removeFeeExemption()
+      .accounts({
+        state: statePDA,
+        feeExemption: feeExemptionPDA,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    
+    assert.isNull(await provider.connection.getAccountInfo(feeExemptionPDA));
+  });
+  
+  it('Sets and permanently lifts the trading limits', async () => {
+    const accounts = { state: statePDA, authority: authority.publicKey };
+    
+    // Sell cooldowns are capped at an hour
+    try {
+      await program.methods
+        .setTradingLimits(new anchor.BN(0), new anchor.BN(0), new anchor.BN(7200))
+        .accounts(accounts)
+        .signers([authority])
+        .rpc();
+      assert.fail('Excessive cooldown was accepted');
+    } catch (error) {
+      assert.include(error.message, 'InvalidTradingLimits');
+    }
+    
+    await program.methods
+      .setTradingLimits(new anchor.BN(0), new anchor.BN(0), new anchor.BN(60))
+      .accounts(accounts)
+      .signers([authority])
+      .rpc();
+    
+    let state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.minSecondsBetweenSells.toNumber(), 60);
+    
+    // Sells into a registered pool are held to the max-tx and cooldown checks
+    const maxTxAmount = 200000;
+    await program.methods
+      .setTradingLimits(new anchor.BN(maxTxAmount), new anchor.BN(0), new anchor.BN(60))
+      .accounts(accounts)
+      .signers([authority])
+      .rpc();
+    
+    const seller = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(
+      seller.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdrop);
+    const sellerAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      seller.publicKey
+    );
+    await mintTo(provider.connection, authority, mint, sellerAta.address, authority, 1000000);
+    
+    const ammOwner = Keypair.generate();
+    const poolTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      ammOwner.publicKey
+    );
+    const poolPDA = (tokenAccount: PublicKey) => PublicKey.findProgramAddressSync(
+      [Buffer.from(REGISTERED_POOL_SEED), tokenAccount.toBuffer()],
+      program.programId
+    )[0];
+    const [poolTaxLedger] = PublicKey.findProgramAddressSync(
+      [Buffer.from('tax-ledger'), poolTokenAccount.address.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerPool()
+      .accounts({
+        state: statePDA,
+        registeredPool: poolPDA(poolTokenAccount.address),
+        poolTaxLedger,
+        poolTokenAccount: poolTokenAccount.address,
+        mint: mint,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const [sellerHolderInfo] = PublicKey.findProgramAddressSync(
+      [Buffer.from('holder'), seller.publicKey.toBuffer()],
+      program.programId
+    );
+    const sell = (amount: number) => program.methods
+      .transferWithTax(new anchor.BN(amount))
+      .accounts({
+        state: statePDA,
+        from: seller.publicKey,
+        fromTokenAccount: sellerAta.address,
+        toTokenAccount: poolTokenAccount.address,
+        fromHolderInfo: sellerHolderInfo,
+        fromExemption: null,
+        toExemption: null,
+        fromPool: poolPDA(sellerAta.address),
+        toPool: poolPDA(poolTokenAccount.address),
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([seller])
+      .rpc();
+    
+    try {
+      await sell(maxTxAmount + 1);
+      assert.fail('Oversized sell was accepted');
+    } catch (error) {
+      assert.include(error.message, 'MaxTransactionExceeded');
+    }
+    
+    await sell(100000);
+    const holderInfo = await program.account.holderInfo.fetch(sellerHolderInfo);
+    assert(holderInfo.lastSellAt.toNumber() > 0);
+    
+    try {
+      await sell(100000);
+      assert.fail('Sell inside the cooldown was accepted');
+    } catch (error) {
+      assert.include(error.message, 'SellCooldownActive');
+    }
+    
+    await program.methods
+      .liftTradingLimits()
+      .accounts(accounts)
+      .signers([authority])
+      .rpc();
+    
+    state = await program.account.mineballState.fetch(statePDA);
+    assert.isTrue(state.limitsLifted);
+    assert.equal(state.minSecondsBetweenSells.toNumber(), 0);
+    
+    // Lifting is one-way
+    try {
+      await program.methods
+        .setTradingLimits(new anchor.BN(0), new anchor.BN(0), new anchor.BN(60))
+        .accounts(accounts)
+        .signers([authority])
+        .rpc();
+      assert.fail('Limits were set after lifting');
+    } catch (error) {
+      assert.include(error.message, 'TradingLimitsLifted');
+    }
+  });
+  
+  it('Sweeps a donation into the mining reward pool', async () => {
+    const before = await program.account.mineballState.fetch(statePDA);
+    
+    // Tokens sent straight to a vault aren't counted by any pool
+    const donation = 1_000_000;
+    await mintTo(provider.connection, authority, mint, liquidityPoolPDA, authority, donation);
+    
+    await program.methods
+      .syncPools()
+      .accounts({
+        state: statePDA,
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        mint: mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.liquidityPool.toString(), before.liquidityPool.toString());
+    assert.equal(
+      state.miningRewardPool.sub(before.miningRewardPool).toNumber(),
+      donation
+    );
+    
+    const liquidityVault = await getAccount(provider.connection, liquidityPoolPDA);
+    assert.equal(liquidityVault.amount.toString(), state.liquidityPool.toString());
+  });
+  
+  it('Compounds only for holders who opted in', async () => {
+    const holder = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(
+      holder.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    
+    const [holderInfoPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('holder'), holder.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    const setPreference = (enabled: boolean) => program.methods
+      .setCompoundPreference(enabled)
+      .accounts({
+        holderInfo: holderInfoPDA,
+        holder: holder.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([holder])
+      .rpc();
+    const compoundFor = () => program.methods
+      .compoundFor()
+      .accounts({
+        state: statePDA,
+        holderInfo: holderInfoPDA,
+        holder: holder.publicKey,
+        caller: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    
+    await setPreference(false);
+    try {
+      await compoundFor();
+      assert.fail('Compounded for a holder who did not opt in');
+    } catch (error) {
+      assert.include(error.message, 'CompoundingDisabled');
+    }
+    
+    await setPreference(true);
+    const holderInfo = await program.account.holderInfo.fetch(holderInfoPDA);
+    assert.isTrue(holderInfo.compound);
+    
+    // Opted in, but nothing has been distributed to the holder yet
+    try {
+      await compoundFor();
+      assert.fail('Compounded without rewards');
+    } catch (error) {
+      assert.include(error.message, 'NoRewardsToClaim');
+    }
+  });
+  
+  it('Stakes and unstakes into the stake vault', async () => {
+    const holder = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(
+      holder.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    
+    const holderAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      holder.publicKey
+    );
+    await mintTo(provider.connection, authority, mint, holderAta.address, authority, 1_000_000);
+    
+    const [stakeVaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(STAKE_VAULT_SEED)],
+      program.programId
+    );
+    const [holderInfoPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('holder'), holder.publicKey.toBuffer()],
+      program.programId
+    );
+    const [stakePositionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(STAKE_SEED), holder.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    await program.methods
+      .initializeStakeVault()
+      .accounts({
+        state: statePDA,
+        stakeVault: stakeVaultPDA,
+        mint: mint,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const before = await program.account.mineballState.fetch(statePDA);
+    const accounts = {
+      state: statePDA,
+      holderInfo: holderInfoPDA,
+      stakePosition: stakePositionPDA,
+      holder: holder.publicKey,
+      holderTokenAccount: holderAta.address,
+      stakeVault: stakeVaultPDA,
+      mint: mint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    
+    await program.methods
+      .stake(new anchor.BN(600_000))
+      .accounts({ ...accounts, systemProgram: SystemProgram.programId })
+      .signers([holder])
+      .rpc();
+    
+    let position = await program.account.stakePosition.fetch(stakePositionPDA);
+    assert.equal(position.amount.toNumber(), 600_000);
+    
+    // Only the staked balance is weighted
+    let holderInfo = await program.account.holderInfo.fetch(holderInfoPDA);
+    assert.equal(holderInfo.rewardBalance.toNumber(), 600_000);
+    
+    try {
+      await program.methods
+        .unstake(new anchor.BN(700_000))
+        .accounts(accounts)
+        .signers([holder])
+        .rpc();
+      assert.fail('Unstaked more than was staked');
+    } catch (error) {
+      assert.include(error.message, 'InsufficientStake');
+    }
+    
+    // The unstake leaves the weight at once but the tokens wait out the cooldown
+    await program.methods
+      .unstake(new anchor.BN(200_000))
+      .accounts(accounts)
+      .signers([holder])
+      .rpc();
+    
+    position = await program.account.stakePosition.fetch(stakePositionPDA);
+    assert.equal(position.amount.toNumber(), 400_000);
+    assert.equal(position.pendingUnstake.toNumber(), 200_000);
+    
+    holderInfo = await program.account.holderInfo.fetch(holderInfoPDA);
+    assert.equal(holderInfo.rewardBalance.toNumber(), 400_000);
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.totalStaked.sub(before.totalStaked).toNumber(), 400_000);
+    
+    let holderAccount = await getAccount(provider.connection, holderAta.address);
+    assert.equal(holderAccount.amount.toString(), '400000');
+    
+    const withdraw = () => program.methods
+      .withdrawUnstaked()
+      .accounts({
+        state: statePDA,
+        stakePosition: stakePositionPDA,
+        holder: holder.publicKey,
+        holderTokenAccount: holderAta.address,
+        stakeVault: stakeVaultPDA,
+        mint: mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([holder])
+      .rpc();
+    
+    try {
+      await withdraw();
+      assert.fail('Withdrew before the cooldown passed');
+    } catch (error) {
+      assert.include(error.message, 'UnstakeCooldownActive');
+    }
+    
+    await new Promise((resolve) => setTimeout(resolve, (UNSTAKE_COOLDOWN + 1) * 1000));
+    await withdraw();
+    
+    position = await program.account.stakePosition.fetch(stakePositionPDA);
+    assert.equal(position.amount.toNumber(), 400_000);
+    assert.equal(position.pendingUnstake.toNumber(), 0);
+    
+    holderAccount = await getAccount(provider.connection, holderAta.address);
+    assert.equal(holderAccount.amount.toString(), '600000');
+  });
+  
+  it('Vests a claim and forfeits the locked part on an early exit', async () => {
+    const holder = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(
+      holder.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    
+    const holderAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      holder.publicKey
+    );
+    await mintTo(provider.connection, authority, mint, holderAta.address, authority, 1_000_000);
+    
+    const pda = (seed: string) => PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), holder.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const [stakeVaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(STAKE_VAULT_SEED)],
+      program.programId
+    );
+    const vestingSchedulePDA = pda('vesting');
+    
+    await program.methods
+      .stake(new anchor.BN(1_000_000))
+      .accounts({
+        state: statePDA,
+        holderInfo: pda('holder'),
+        stakePosition: pda(STAKE_SEED),
+        holder: holder.publicKey,
+        holderTokenAccount: holderAta.address,
+        stakeVault: stakeVaultPDA,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([holder])
+      .rpc();
+    
+    // Fill the mining reward pool up to the threshold and distribute it to stakers
+    let state = await program.account.mineballState.fetch(statePDA);
+    const topUp = state.rewardDistributionThreshold.sub(state.miningRewardPool);
+    await mintTo(provider.connection, authority, mint, rewardPoolPDA, authority, BigInt(topUp.toString()));
+    await program.methods
+      .syncPools()
+      .accounts({
+        state: statePDA,
+        rewardPool: rewardPoolPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        mint: mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const callerAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      authority.publicKey
+    );
+    await program.methods
+      .distributeRewards(null)
+      .accounts({
+        state: statePDA,
+        rewardPool: rewardPoolPDA,
+        mint: mint,
+        caller: authority.publicKey,
+        callerTokenAccount: callerAta.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardEpoch: null,
+        authority: null,
+        systemProgram: null,
+      })
+      .signers([authority])
+      .rpc();
+    
+    // The claim is locked instead of paid out
+    const before = await program.account.mineballState.fetch(statePDA);
+    await program.methods
+      .claimRewards()
+      .accounts({
+        state: statePDA,
+        holderInfo: pda('holder'),
+        vestingSchedule: vestingSchedulePDA,
+        holder: holder.publicKey,
+        holderTokenAccount: holderAta.address,
+        rewardPool: rewardPoolPDA,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([holder])
+      .rpc();
+    
+    let schedule = await program.account.vestingSchedule.fetch(vestingSchedulePDA);
+    const claimed = schedule.lockedAmount.toNumber();
+    assert(claimed > 0);
+    assert.equal(schedule.end.sub(schedule.start).toNumber(), VESTING_DURATION);
+    
+    state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.vestingRewards.sub(before.vestingRewards).toNumber(), claimed);
+    assert.equal(Number((await getAccount(provider.connection, holderAta.address)).amount), 0);
+    
+    // Exit half-way: the unlocked part is paid, the locked part goes back to the pool
+    await new Promise((resolve) => setTimeout(resolve, (VESTING_DURATION / 2) * 1000));
+    const vaultBefore = Number((await getAccount(provider.connection, rewardPoolPDA)).amount);
+    await program.methods
+      .withdrawVested(true)
+      .accounts({
+        state: statePDA,
+        vestingSchedule: vestingSchedulePDA,
+        holder: holder.publicKey,
+        holderTokenAccount: holderAta.address,
+        rewardPool: rewardPoolPDA,
+        mint: mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([holder])
+      .rpc();
+    
+    schedule = await program.account.vestingSchedule.fetch(vestingSchedulePDA);
+    const withdrawn = schedule.totalWithdrawn.toNumber();
+    const forfeited = schedule.totalForfeited.toNumber();
+    assert(withdrawn > 0 && forfeited > 0);
+    assert.equal(withdrawn + forfeited, claimed);
+    assert.equal(schedule.lockedAmount.toNumber(), 0);
+    
+    assert.equal(Number((await getAccount(provider.connection, holderAta.address)).amount), withdrawn);
+    const vaultAfter = Number((await getAccount(provider.connection, rewardPoolPDA)).amount);
+    assert.equal(vaultBefore - vaultAfter, withdrawn);
+    
+    const after = await program.account.mineballState.fetch(statePDA);
+    assert.equal(after.miningRewardPool.sub(state.miningRewardPool).toNumber(), forfeited);
+    assert.equal(after.vestingRewards.sub(before.vestingRewards).toNumber(), 0);
+  });
+  
+  it('Refuses to complete a mining cycle before its minimum duration', async () => {
+    const cyclePDA = (cycle: number) => PublicKey.findProgramAddressSync(
+      [Buffer.from(MINING_CYCLE_SEED), new anchor.BN(cycle).toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    )[0];
+    
+    try {
+      await program.methods
+        .completeCycle()
+        .accounts({
+          state: statePDA,
+          currentCycle: cyclePDA(1),
+          nextCycle: cyclePDA(2),
+          payer: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      assert.fail('Cycle completed early');
+    } catch (error) {
+      assert.include(error.message, 'CycleNotComplete');
+    }
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.totalMiningCycles.toNumber(), 0);
+    assert.isNull(await provider.connection.getAccountInfo(cyclePDA(1)));
+  });
+  
+  it('Penalizes a loose crank and still completes the mining cycle', async () => {
+    // Everything the buyback test set up is pinned on the state
+    const before = await program.account.mineballState.fetch(statePDA);
+    const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const cyclePDA = (cycle: number) => pda(
+      Buffer.from(MINING_CYCLE_SEED),
+      new anchor.BN(cycle).toArrayLike(Buffer, 'le', 8)
+    );
+    const buybackVaultPDA = pda(Buffer.from(BUYBACK_VAULT_SEED));
+    const keeperRegistryPDA = pda(Buffer.from(KEEPER_REGISTRY_SEED));
+    const keeperInfoPDA = pda(Buffer.from(KEEPER_SEED), authority.publicKey.toBuffer());
+    
+    const executorTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      authority.publicKey
+    );
+    const executorQuoteAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      before.quoteMint,
+      authority.publicKey
+    );
+    
+    // Fill the buyback vault so the crank has a trigger to run
+    await mintTo(
+      provider.connection,
+      authority,
+      before.quoteMint,
+      buybackVaultPDA,
+      authority,
+      BigInt(before.buybackThreshold.toString())
+    );
+    
+    const crank = (cycle: number) => program.methods
+      .crank({
+        // No floor at all is looser than the registry allows
+        minBuybackOut: new anchor.BN(0),
+        minQuoteOut: new anchor.BN(0),
+        minLpOut: new anchor.BN(0),
+        deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 60),
+      })
+      .accounts({
+        state: statePDA,
+        rewardPool: rewardPoolPDA,
+        buybackVault: buybackVaultPDA,
+        buybackPool: buybackPoolPDA,
+        liquidityPool: liquidityPoolPDA,
+        liquidityQuoteVault: pda(Buffer.from(LIQUIDITY_QUOTE_VAULT_SEED)),
+        lpVault: pda(Buffer.from(LP_VAULT_SEED)),
+        mint: mint,
+        quoteMint: before.quoteMint,
+        lpMint: before.lpMint,
+        ammProgram: before.ammProgram,
+        ammPool: before.ammPool,
+        ammQuoteVault: getAssociatedTokenAddressSync(before.quoteMint, before.ammPool, true),
+        ammTokenVault: getAssociatedTokenAddressSync(mint, before.ammPool, true),
+        keeperRegistry: keeperRegistryPDA,
+        keeperInfo: keeperInfoPDA,
+        executor: authority.publicKey,
+        executorTokenAccount: executorTokenAccount.address,
+        executorQuoteAccount: executorQuoteAccount.address,
+        currentCycle: cyclePDA(cycle),
+        nextCycle: cyclePDA(cycle + 1),
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    await crank(1);
+    
+    // The keeper was penalized and nothing was bought
+    const keeperInfo = await program.account.keeper.fetch(keeperInfoPDA);
+    assert.equal(keeperInfo.slashCount, 1);
+    assert(keeperInfo.cooldownUntil.toNumber() > Math.floor(Date.now() / 1000));
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.totalBuybackExecuted.toString(), before.totalBuybackExecuted.toString());
+    
+    // The cycle still closed, so the next crank finds the cycle accounts it expects
+    assert.equal(state.totalMiningCycles.toNumber(), before.totalMiningCycles.toNumber() + 1);
+    assert.isNotNull(await provider.connection.getAccountInfo(cyclePDA(2)));
+    
+    // A penalized keeper is locked out
+    try {
+      await crank(2);
+      assert.fail('Keeper in cooldown cranked');
+    } catch (error) {
+      assert.include(error.message, 'KeeperInCooldown');
+    }
+  });
+  
+  it('Closes a completed mining cycle and refunds its rent', async () => {
+    const state = await program.account.mineballState.fetch(statePDA);
+    const cyclePDA = (cycle: number) => PublicKey.findProgramAddressSync(
+      [Buffer.from(MINING_CYCLE_SEED), new anchor.BN(cycle).toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    )[0];
+    const completed = state.totalMiningCycles.toNumber();
+    const open = completed + 1;
+    
+    // The keeper paid for both cycles of its crank
+    const cycle = await program.account.miningCycle.fetch(cyclePDA(completed));
+    assert(cycle.completedAt.toNumber() > 0);
+    assert.equal(cycle.payer.toBase58(), authority.publicKey.toBase58());
+    
+    const close = (number: number, payer: PublicKey) => program.methods
+      .closeCycle(new anchor.BN(number))
+      .accounts({
+        miningCycle: cyclePDA(number),
+        payer,
+      })
+      .rpc();
+    
+    // The rent only goes back to whoever paid it
+    try {
+      await close(completed, provider.wallet.publicKey);
+      assert.fail('Rent was refunded to another account');
+    } catch (error) {
+      assert.include(error.message, 'InvalidRentPayer');
+    }
+    
+    // The open cycle is still needed by the next crank
+    try {
+      await close(open, authority.publicKey);
+      assert.fail('Closed the open cycle');
+    } catch (error) {
+      assert.include(error.message, 'CycleStillOpen');
+    }
+    
+    // Anyone may close it, the fee payer here isn't the one refunded
+    const rent = await provider.connection.getBalance(cyclePDA(completed));
+    const balanceBefore = await provider.connection.getBalance(authority.publicKey);
+    await close(completed, authority.publicKey);
+    
+    assert.isNull(await provider.connection.getAccountInfo(cyclePDA(completed)));
+    const balanceAfter = await provider.connection.getBalance(authority.publicKey);
+    assert.equal(balanceAfter - balanceBefore, rent);
+  });
+  
+  it('Commits a Merkle reward epoch and pays a proven claim', async () => {
+    const REWARD_EPOCH_SEED = 'reward-epoch';
+    const before = await program.account.mineballState.fetch(statePDA);
+    
+    // Fill the mining reward pool up to the distribution threshold
+    const fillRewardPool = async () => {
+      const state = await program.account.mineballState.fetch(statePDA);
+      const topUp = state.rewardDistributionThreshold.sub(state.miningRewardPool);
+      await mintTo(provider.connection, authority, mint, rewardPoolPDA, authority, BigInt(topUp.toString()));
+      await program.methods
+        .syncPools()
+        .accounts({
+          state: statePDA,
+          rewardPool: rewardPoolPDA,
+          buybackPool: buybackPoolPDA,
+          liquidityPool: liquidityPoolPDA,
+          mint: mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      return (await program.account.mineballState.fetch(statePDA)).miningRewardPool.toNumber();
+    };
+    const pool = await fillRewardPool();
+    
+    // Two-leaf tree, hashed the way the program does
+    const leaf = (index: number, holder: PublicKey, amount: number) => Buffer.from(keccak_256(Buffer.concat([
+      Buffer.from([0]),
+      new anchor.BN(index).toArrayLike(Buffer, 'le', 4),
+      holder.toBuffer(),
+      new anchor.BN(amount).toArrayLike(Buffer, 'le', 8),
+    ])));
+    const node = (a: Buffer, b: Buffer) => Buffer.from(keccak_256(Buffer.concat(
+      [Buffer.from([1]), ...(Buffer.compare(a, b) <= 0 ? [a, b] : [b, a])]
+    )));
+    
+    const holders = [Keypair.generate(), Keypair.generate()];
+    const amounts = [400_000_000_000, 500_000_000_000];
+    const leaves = holders.map((holder, i) => leaf(i, holder.publicKey, amounts[i]));
+    const merkleRoot = node(leaves[0], leaves[1]);
+    
+    // Epochs are numbered by the state, not by the mining cycle
+    const epochPDA = (epochNumber: number) => PublicKey.findProgramAddressSync(
+      [Buffer.from(REWARD_EPOCH_SEED), new anchor.BN(epochNumber).toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    )[0];
+    const epochNumber = before.epochCount.toNumber() + 1;
+    const rewardEpochPDA = epochPDA(epochNumber);
+    const callerAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      authority.publicKey
+    );
+    const callerBefore = Number((await getAccount(provider.connection, callerAta.address)).amount);
+    
+    const commit = (rewardEpoch: PublicKey) => program.methods
+      .distributeRewards({ merkleRoot: [...merkleRoot], recipients: 2 })
+      .accounts({
+        state: statePDA,
+        rewardPool: rewardPoolPDA,
+        mint: mint,
+        caller: authority.publicKey,
+        callerTokenAccount: callerAta.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardEpoch,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    await commit(rewardEpochPDA);
+    
+    // The caller's bounty came off the top, the rest was set aside for the epoch
+    const keeperBounty = Math.floor((pool * 10) / 10_000);
+    const callerAfter = Number((await getAccount(provider.connection, callerAta.address)).amount);
+    assert.equal(callerAfter - callerBefore, keeperBounty);
+    
+    let epoch = await program.account.rewardEpoch.fetch(rewardEpochPDA);
+    assert.equal(epoch.epoch.toNumber(), epochNumber);
+    assert.equal(epoch.cycle.toNumber(), before.totalMiningCycles.toNumber() + 1);
+    assert.equal(epoch.totalAmount.toNumber(), pool - keeperBounty);
+    assert.deepEqual(epoch.merkleRoot, [...merkleRoot]);
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.epochCount.toNumber(), epochNumber);
+    assert.equal(state.miningRewardPool.toNumber(), 0);
+    assert.equal(
+      state.unclaimedRewards.sub(before.unclaimedRewards).toNumber(),
+      pool - keeperBounty
+    );
+    
+    const holderAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      holders[0].publicKey
+    );
+    const claim = (amount: number, proof: Buffer[]) => program.methods
+      .claimEpochReward(new anchor.BN(epochNumber), 0, new anchor.BN(amount), proof.map((p) => [...p]))
+      .accounts({
+        state: statePDA,
+        rewardEpoch: rewardEpochPDA,
+        holder: holders[0].publicKey,
+        holderTokenAccount: holderAta.address,
+        rewardPool: rewardPoolPDA,
+        mint: mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([holders[0]])
+      .rpc();
+    
+    // A proof for any other amount doesn't hash to the root
+    try {
+      await claim(amounts[1], [leaves[1]]);
+      assert.fail('Claimed with a forged amount');
+    } catch (error) {
+      assert.include(error.message, 'InvalidMerkleProof');
+    }
+    
+    await claim(amounts[0], [leaves[1]]);
+    
+    const holderAccount = await getAccount(provider.connection, holderAta.address);
+    assert.equal(Number(holderAccount.amount), amounts[0]);
+    
+    epoch = await program.account.rewardEpoch.fetch(rewardEpochPDA);
+    assert.equal(epoch.claimedAmount.toNumber(), amounts[0]);
+    assert.equal(epoch.claimedCount, 1);
+    
+    try {
+      await claim(amounts[0], [leaves[1]]);
+      assert.fail('Claimed the same leaf twice');
+    } catch (error) {
+      assert.include(error.message, 'AlreadyClaimed');
+    }
+    
+    // The second holder still has the whole claim window
+    try {
+      await program.methods
+        .closeEpoch(new anchor.BN(epochNumber))
+        .accounts({
+          state: statePDA,
+          rewardEpoch: rewardEpochPDA,
+          caller: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+      assert.fail('Closed an epoch inside its claim window');
+    } catch (error) {
+      assert.include(error.message, 'EpochNotExpired');
+    }
+    
+    // A second snapshot in the same mining cycle gets an epoch of its own
+    await fillRewardPool();
+    await commit(epochPDA(epochNumber + 1));
+    
+    const next = await program.account.rewardEpoch.fetch(epochPDA(epochNumber + 1));
+    assert.equal(next.epoch.toNumber(), epochNumber + 1);
+    assert.equal(next.cycle.toNumber(), epoch.cycle.toNumber());
+    assert.equal(
+      (await program.account.mineballState.fetch(statePDA)).epochCount.toNumber(),
+      epochNumber + 1
+    );
+  });
+  
+  it('Slashes a bonded keeper and releases what is left of its bond', async () => {
+    const state = await program.account.mineballState.fetch(statePDA);
+    const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const keeperRegistryPDA = pda(Buffer.from(KEEPER_REGISTRY_SEED));
+    const keeper = Keypair.generate();
+    const keeperInfoPDA = pda(Buffer.from(KEEPER_SEED), keeper.publicKey.toBuffer());
+    const bond = anchor.web3.LAMPORTS_PER_SOL;
+    
+    const signature = await provider.connection.requestAirdrop(
+      keeper.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    
+    const registerAccounts = {
+      state: statePDA,
+      keeperRegistry: keeperRegistryPDA,
+      keeperInfo: keeperInfoPDA,
+      keeper: keeper.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    
+    // The registry is permissioned, so the authority has to co-sign
+    try {
+      await program.methods
+        .registerKeeper(new anchor.BN(bond))
+        .accounts({ ...registerAccounts, authority: null })
+        .signers([keeper])
+        .rpc();
+      assert.fail('Registered without the authority');
+    } catch (error) {
+      assert.include(error.message, 'Unauthorized');
+    }
+    
+    await program.methods
+      .registerKeeper(new anchor.BN(bond))
+      .accounts({ ...registerAccounts, authority: authority.publicKey })
+      .signers([keeper, authority])
+      .rpc();
+    
+    let keeperInfo = await program.account.keeper.fetch(keeperInfoPDA);
+    assert.equal(keeperInfo.bond.toNumber(), bond);
+    const registry = await program.account.keeperRegistry.fetch(keeperRegistryPDA);
+    
+    // The crank test left the buyback vault full, so this trigger runs, with no floor at all
+    const keeperQuoteAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      state.quoteMint,
+      keeper.publicKey
+    );
+    await program.methods
+      .executeBuyback(new anchor.BN(0), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({
+        state: statePDA,
+        buybackVault: pda(Buffer.from(BUYBACK_VAULT_SEED)),
+        buybackPool: buybackPoolPDA,
+        quoteMint: state.quoteMint,
+        mint: mint,
+        ammProgram: state.ammProgram,
+        ammPool: state.ammPool,
+        ammQuoteVault: getAssociatedTokenAddressSync(state.quoteMint, state.ammPool, true),
+        ammTokenVault: getAssociatedTokenAddressSync(mint, state.ammPool, true),
+        keeperRegistry: keeperRegistryPDA,
+        keeperInfo: keeperInfoPDA,
+        executor: keeper.publicKey,
+        executorQuoteAccount: keeperQuoteAccount.address,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([keeper])
+      .rpc();
+    
+    // 10% of the bond moved into the registry
+    const slashed = bond / 10;
+    keeperInfo = await program.account.keeper.fetch(keeperInfoPDA);
+    assert.equal(keeperInfo.bond.toNumber(), bond - slashed);
+    assert.equal(keeperInfo.slashCount, 1);
+    const slashedRegistry = await program.account.keeperRegistry.fetch(keeperRegistryPDA);
+    assert.equal(slashedRegistry.totalSlashed.sub(registry.totalSlashed).toNumber(), slashed);
+    
+    const withdrawAccounts = {
+      state: statePDA,
+      keeperRegistry: keeperRegistryPDA,
+      authority: authority.publicKey,
+    };
+    const authorityBefore = await provider.connection.getBalance(authority.publicKey);
+    await program.methods
+      .withdrawSlashedBonds()
+      .accounts(withdrawAccounts)
+      .signers([authority])
+      .rpc();
+    const authorityAfter = await provider.connection.getBalance(authority.publicKey);
+    assert.equal(authorityAfter - authorityBefore, slashed);
+    
+    try {
+      await program.methods
+        .withdrawSlashedBonds()
+        .accounts(withdrawAccounts)
+        .signers([authority])
+        .rpc();
+      assert.fail('Withdrew the slashed bonds twice');
+    } catch (error) {
+      assert.include(error.message, 'NoSlashedBonds');
+    }
+    
+    const deregisterAccounts = {
+      state: statePDA,
+      keeperRegistry: keeperRegistryPDA,
+      keeperInfo: keeperInfoPDA,
+      keeper: keeper.publicKey,
+    };
+    
+    // A slashed keeper can't walk away before its cooldown ends
+    try {
+      await program.methods
+        .deregisterKeeper()
+        .accounts({ ...deregisterAccounts, caller: keeper.publicKey })
+        .signers([keeper])
+        .rpc();
+      assert.fail('Keeper left during its cooldown');
+    } catch (error) {
+      assert.include(error.message, 'KeeperInCooldown');
+    }
+    
+    // The authority can always remove it, which returns the rest of the bond
+    const keeperBefore = await provider.connection.getBalance(keeper.publicKey);
+    await program.methods
+      .deregisterKeeper()
+      .accounts({ ...deregisterAccounts, caller: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    const keeperAfter = await provider.connection.getBalance(keeper.publicKey);
+    assert(keeperAfter - keeperBefore > bond - slashed);
+    assert.isNull(await provider.connection.getAccountInfo(keeperInfoPDA));
+    
+    const finalRegistry = await program.account.keeperRegistry.fetch(keeperRegistryPDA);
+    assert.equal(finalRegistry.keeperCount.toNumber(), registry.keeperCount.toNumber() - 1);
+  });
+  
+  it('Refuses to harvest withheld fees outside transfer-fee mode', async () => {
+    // This deployment charges tax by instruction, so there are no withheld fees
+    try {
+      await program.methods
+        .harvestAndSplit()
+        .accounts({
+          state: statePDA,
+          mint: mint,
+          rewardPool: rewardPoolPDA,
+          buybackPool: buybackPoolPDA,
+          liquidityPool: liquidityPoolPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail('Harvested fees outside transfer-fee mode');
+    } catch (error) {
+      assert.include(error.message, 'InvalidTaxMode');
+    }
+  });
+  
+  it('Opens tax ledgers but refuses hook bookkeeping outside transfer-hook mode', async () => {
+    const TAX_LEDGER_SEED = 'tax-ledger';
+    const FEE_EXEMPTION_SEED = 'fee-exemption';
+    const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const poolAccounts = {
+      rewardPool: rewardPoolPDA,
+      buybackPool: buybackPoolPDA,
+      liquidityPool: liquidityPoolPDA,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    
+    const holder = Keypair.generate();
+    const buyer = Keypair.generate();
+    const holderAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      holder.publicKey
+    );
+    const buyerAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      buyer.publicKey
+    );
+    const ledgerPDA = (tokenAccount: PublicKey) => pda(Buffer.from(TAX_LEDGER_SEED), tokenAccount.toBuffer());
+    
+    // Anyone can open a tax ledger, ready for when a hook-mode mint needs it
+    await program.methods
+      .initializeTaxLedger()
+      .accounts({
+        state: statePDA,
+        taxLedger: ledgerPDA(holderAta.address),
+        tokenAccount: holderAta.address,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    
+    const ledger = await program.account.taxLedger.fetch(ledgerPDA(holderAta.address));
+    assert(ledger.tokenAccount.equals(holderAta.address));
+    assert.equal(ledger.taxOwed.toNumber(), 0);
+    
+    // The hook's bookkeeping is refused outright
+    try {
+      await program.methods
+        .recordHookTransfer(new anchor.BN(1_000))
+        .accounts({
+          state: statePDA,
+          sourceTokenAccount: holderAta.address,
+          mint: mint,
+          destinationTokenAccount: buyerAta.address,
+          sourceTaxLedger: ledgerPDA(holderAta.address),
+          destinationTaxLedger: ledgerPDA(buyerAta.address),
+          sourceExemption: pda(Buffer.from(FEE_EXEMPTION_SEED), holder.publicKey.toBuffer()),
+          destinationExemption: pda(Buffer.from(FEE_EXEMPTION_SEED), buyer.publicKey.toBuffer()),
+          sourcePool: pda(Buffer.from(REGISTERED_POOL_SEED), holderAta.address.toBuffer()),
+          destinationPool: pda(Buffer.from(REGISTERED_POOL_SEED), buyerAta.address.toBuffer()),
+        })
+        .rpc();
+      assert.fail('Recorded a hook transfer outside transfer-hook mode');
+    } catch (error) {
+      assert.include(error.message, 'InvalidTaxMode');
+    }
+    
+    try {
+      await program.methods
+        .collectHookTax()
+        .accounts({
+          state: statePDA,
+          mint: mint,
+          tokenAccount: holderAta.address,
+          taxLedger: ledgerPDA(holderAta.address),
+          ...poolAccounts,
+        })
+        .rpc();
+      assert.fail('Collected hook tax outside transfer-hook mode');
+    } catch (error) {
+      assert.include(error.message, 'InvalidTaxMode');
+    }
+  });
+  
+  it('Queues config changes and hands over the authority', async () => {
+    const PENDING_CONFIG_SEED = 'pending-config';
+    const MIN_CONFIG_DELAY = 172_800;
+    const MAX_VESTING_DURATION = 31_536_000;
+    const [pendingConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(PENDING_CONFIG_SEED)],
+      program.programId
+    );
+    
+    const update = {
+      buyTaxRate: null,
+      sellTaxRate: null,
+      transferTaxRate: null,
+      buyShares: null,
+      sellShares: null,
+      transferShares: {
+        miningRewardShare: 3500,
+        buybackShare: 3000,
+        liquidityShare: 2500,
+        burnShare: 1000,
+      },
+      volumeTiers: null,
+      keeperBountyBps: null,
+      vestingDuration: null,
+      unstakeCooldown: null,
+      rewardDistributionThreshold: null,
+      buybackThreshold: null,
+      liquidityThreshold: new anchor.BN(250_000_000_000),
+    };
+    const now = Math.floor(Date.now() / 1000);
+    const accounts = {
+      state: statePDA,
+      pendingConfigChange: pendingConfigPDA,
+      authority: authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    
+    // Changes need at least the minimum notice
+    try {
+      await program.methods
+        .queueConfigChange(update, new anchor.BN(now + 60))
+        .accounts(accounts)
+        .signers([authority])
+        .rpc();
+      assert.fail('Short delay was accepted');
+    } catch (error) {
+      assert.include(error.message, 'ConfigDelayTooShort');
+    }
+    
+    // Shares that don't sum to 100% are rejected when queued
+    try {
+      await program.methods
+        .queueConfigChange(
+          { ...update, sellShares: { ...update.transferShares, liquidityShare: 2000 } },
+          new anchor.BN(now + MIN_CONFIG_DELAY + 60)
+        )
+        .accounts(accounts)
+        .signers([authority])
+        .rpc();
+      assert.fail('Invalid shares were accepted');
+    } catch (error) {
+      assert.include(error.message, 'InvalidTaxShares');
+    }
+    
+    // So are vesting periods over the maximum
+    try {
+      await program.methods
+        .queueConfigChange(
+          { ...update, vestingDuration: new anchor.BN(MAX_VESTING_DURATION + 1) },
+          new anchor.BN(now + MIN_CONFIG_DELAY + 60)
+        )
+        .accounts(accounts)
+        .signers([authority])
+        .rpc();
+      assert.fail('Overlong vesting period was accepted');
+    } catch (error) {
+      assert.include(error.message, 'InvalidVestingDuration');
+    }
+    
+    const eta = now + MIN_CONFIG_DELAY + 60;
+    await program.methods
+      .queueConfigChange(update, new anchor.BN(eta))
+      .accounts(accounts)
+      .signers([authority])
+      .rpc();
+    
+    const pending = await program.account.pendingConfigChange.fetch(pendingConfigPDA);
+    assert.equal(pending.eta.toNumber(), eta);
+    
+    // Still timelocked
+    try {
+      await program.methods
+        .executeConfigChange()
+        .accounts({ state: statePDA, pendingConfigChange: pendingConfigPDA, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      assert.fail('Timelocked change was executed');
+    } catch (error) {
+      assert.include(error.message, 'ConfigChangeNotReady');
+    }
+    
+    await program.methods
+      .cancelConfigChange()
+      .accounts({ state: statePDA, pendingConfigChange: pendingConfigPDA, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    
+    const state = await program.account.mineballState.fetch(statePDA);
+    assert.equal(state.transferShares.miningRewardShare, 4000);
+    
+    // Two-step transfer to a new authority, which then renounces
+    const newAuthority = Keypair.generate();
+    
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({ state: statePDA, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    
+    await program.methods
+      .acceptAuthority()
+      .accounts({ state: statePDA, pendingAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    
+    await program.methods
+      .renounceAuthority()
+      .accounts({ state: statePDA, authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    
+    const renounced = await program.account.mineballState.fetch(statePDA);
+    assert(renounced.authority.equals(PublicKey.default));
+  });
 });